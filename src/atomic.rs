@@ -0,0 +1,321 @@
+#[cfg(not(loom))]
+use core::sync::atomic::{AtomicU64, Ordering};
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{ClockError, HybridLogicalClock, OverflowPolicy, PackedLayout};
+
+/// A hybrid logical clock that can be shared between threads without a lock.
+///
+/// The physical and logical components are packed into a single [`AtomicU64`] according
+/// to a [`PackedLayout`]. [`AtomicHlc::tick`] and [`AtomicHlc::update`] are compare-and-swap
+/// loops, so every caller receives a distinct timestamp and the clock never goes backwards.
+///
+/// With the default layout of 48 physical and 16 logical bits, the physical component is
+/// enough for milliseconds since the Unix epoch until the year 10889.
+///
+/// The clock counts in the layout's [`TimeUnit`](crate::TimeUnit). With a unit coarser than
+/// milliseconds, the physical time passed to [`AtomicHlc::tick`] and
+/// [`AtomicHlc::update`] is rounded down to a whole unit, and a received timestamp between
+/// two units is rounded up, so the clock still ends up ahead of it.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{AtomicHlc, HybridLogicalClock};
+///
+/// let clock = AtomicHlc::new(HybridLogicalClock::new(1000)).unwrap();
+///
+/// let t1 = clock.tick(1000).unwrap();
+/// let t2 = clock.tick(999).unwrap();
+/// assert!(t2 > t1);
+/// assert_eq!(clock.load(), t2);
+/// ```
+#[derive(Debug)]
+pub struct AtomicHlc {
+    state: AtomicU64,
+    layout: PackedLayout,
+}
+
+impl AtomicHlc {
+    /// Creates a new AtomicHlc starting at `hlc`, with the default [`PackedLayout`].
+    ///
+    /// # Returns
+    ///
+    /// The new clock, or an error if `hlc` does not fit in the packed representation.
+    pub fn new(hlc: HybridLogicalClock) -> Result<Self, ClockError> {
+        Self::with_layout(hlc, PackedLayout::default())
+    }
+
+    /// Creates a new AtomicHlc starting at `hlc`, packing the clock with `layout`.
+    ///
+    /// # Returns
+    ///
+    /// The new clock, or an error if `hlc` does not fit in the packed representation.
+    pub fn with_layout(hlc: HybridLogicalClock, layout: PackedLayout) -> Result<Self, ClockError> {
+        let packed = layout.pack(&hlc)?;
+        Ok(Self {
+            state: AtomicU64::new(packed),
+            layout,
+        })
+    }
+
+    /// Returns the layout the clock is packed with.
+    pub fn layout(&self) -> &PackedLayout {
+        &self.layout
+    }
+
+    /// Returns the last timestamp issued by the clock without advancing it.
+    pub fn load(&self) -> HybridLogicalClock {
+        self.unpack(self.state.load(Ordering::Acquire))
+    }
+
+    /// Advances the clock for a local or send event and returns the new timestamp.
+    ///
+    /// See [`HybridLogicalClock::tick`]. If the logical component would not fit in its
+    /// bits, the physical component is advanced by one unit instead.
+    ///
+    /// # Returns
+    ///
+    /// The new timestamp, or [`ClockError::PhysicalOutOfRange`] if the physical component
+    /// no longer fits in its bits. The clock is unchanged on error.
+    pub fn tick(&self, now: u64) -> Result<HybridLogicalClock, ClockError> {
+        let now = self.units(now)?;
+        self.advance(|current| current.send_rule(now))
+    }
+
+    /// Updates the clock based on a received timestamp and returns the new timestamp.
+    ///
+    /// See [`HybridLogicalClock::update`]. If the logical component would not fit in its
+    /// bits, the physical component is advanced by one unit instead.
+    ///
+    /// # Returns
+    ///
+    /// The new timestamp, [`ClockError::LogicalLimitExceeded`] if the received logical
+    /// component does not fit in the packed representation, or
+    /// [`ClockError::PhysicalOutOfRange`] if the new physical component does not. The clock
+    /// is unchanged on error.
+    pub fn update(
+        &self,
+        received: &HybridLogicalClock,
+        now: u64,
+    ) -> Result<HybridLogicalClock, ClockError> {
+        let max_logical = self.layout.max_logical();
+        if received.logical > max_logical {
+            return Err(ClockError::LogicalLimitExceeded {
+                received: received.logical,
+                max: max_logical,
+            });
+        }
+        let now = self.units(now)?;
+        let physical = self.units(received.physical)?;
+        let received = if self.layout.millis(physical) == Ok(received.physical) {
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                physical,
+                received.logical,
+            )
+        } else {
+            HybridLogicalClock::new(physical.saturating_add(1))
+        };
+        self.advance(|current| current.receive_rule(&received, now))
+    }
+
+    /// Converts a physical time in milliseconds to the layout's unit, rounding down.
+    fn units(&self, millis: u64) -> Result<u64, ClockError> {
+        self.layout
+            .unit()
+            .to_units(millis)
+            .ok_or(self.layout.physical_out_of_range(millis))
+    }
+
+    /// Runs a compare-and-swap loop moving the clock to the state computed by `rule`.
+    ///
+    /// `rule` sees the clock with its physical component in the layout's unit.
+    fn advance(
+        &self,
+        rule: impl Fn(&HybridLogicalClock) -> (u64, u64),
+    ) -> Result<HybridLogicalClock, ClockError> {
+        let mut packed = self.state.load(Ordering::Acquire);
+        loop {
+            let (physical, logical) = self
+                .layout
+                .unpack_units(packed)
+                .expect("stored value matches the layout");
+            let mut hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                physical, logical,
+            );
+            let next = rule(&hlc);
+            let next = hlc.advance(
+                next,
+                self.layout.max_logical(),
+                OverflowPolicy::BorrowPhysical,
+            )?;
+            let issued = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                self.layout.millis(next.physical)?,
+                next.logical,
+            );
+            match self.state.compare_exchange_weak(
+                packed,
+                self.layout.pack_units(next.physical, next.logical)?,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(issued),
+                Err(actual) => packed = actual,
+            }
+        }
+    }
+
+    fn unpack(&self, packed: u64) -> HybridLogicalClock {
+        // Only values packed with our own layout are ever stored.
+        self.layout
+            .unpack(packed)
+            .expect("stored value matches the layout")
+    }
+}
+
+#[cfg(all(test, not(loom)))]
+mod tests {
+    use super::*;
+    use crate::TimeUnit;
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_tick_and_update() {
+        let clock = AtomicHlc::new(HybridLogicalClock::new(100)).unwrap();
+        assert_eq!(clock.tick(200), Ok(HybridLogicalClock::new(200)));
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(300, 4);
+        assert_eq!(
+            clock.update(&received, 250),
+            Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(300, 5))
+        );
+        assert_eq!(
+            clock.load(),
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(300, 5)
+        );
+    }
+
+    #[test]
+    fn test_bit_split() {
+        let start = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(7, 3);
+        let clock = AtomicHlc::with_layout(start, PackedLayout::new(62, 2, TimeUnit::Milliseconds))
+            .unwrap();
+        assert_eq!(clock.tick(0), Ok(HybridLogicalClock::new(8)));
+
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(8, 4);
+        assert_eq!(
+            clock.update(&received, 0),
+            Err(ClockError::LogicalLimitExceeded {
+                received: 4,
+                max: 3
+            })
+        );
+
+        let layout = PackedLayout::new(32, 32, TimeUnit::Milliseconds);
+        let clock = AtomicHlc::with_layout(HybridLogicalClock::new(0), layout).unwrap();
+        assert_eq!(
+            clock.tick(u64::from(u32::MAX) + 1),
+            Err(ClockError::PhysicalOutOfRange {
+                physical: u64::from(u32::MAX) + 1,
+                max: u64::from(u32::MAX)
+            })
+        );
+        assert_eq!(clock.load(), HybridLogicalClock::new(0));
+        assert!(AtomicHlc::new(HybridLogicalClock::new(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_seconds() {
+        let layout = PackedLayout::new(32, 32, TimeUnit::Seconds);
+        let clock = AtomicHlc::with_layout(HybridLogicalClock::new(1000), layout).unwrap();
+        // Ticks within the same second count logically.
+        assert_eq!(
+            clock.tick(1999),
+            Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 1))
+        );
+        assert_eq!(clock.tick(2500), Ok(HybridLogicalClock::new(2000)));
+
+        // A received timestamp between two seconds moves the clock to the next second.
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(4200, 9);
+        let updated = clock.update(&received, 2500).unwrap();
+        assert_eq!(
+            updated,
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(5000, 1)
+        );
+        assert!(updated > received);
+        assert_eq!(clock.load(), updated);
+    }
+
+    #[test]
+    fn test_concurrent_ticks_are_unique() {
+        const THREADS: usize = 8;
+        const TICKS: usize = 1000;
+        let clock = Arc::new(AtomicHlc::new(HybridLogicalClock::new(0)).unwrap());
+        let handles: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                let clock = Arc::clone(&clock);
+                thread::spawn(move || {
+                    let mut issued = Vec::with_capacity(TICKS);
+                    for i in 0..TICKS {
+                        // Every thread reads a slightly different, sometimes stale, wall clock.
+                        let now = (i / 10 + thread) as u64;
+                        issued.push(clock.tick(now).unwrap());
+                    }
+                    issued
+                })
+            })
+            .collect();
+
+        let mut all = Vec::new();
+        for handle in handles {
+            let issued = handle.join().unwrap();
+            assert!(issued.windows(2).all(|pair| pair[0] < pair[1]));
+            all.extend(issued);
+        }
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), THREADS * TICKS);
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn concurrent_ticks_are_distinct_and_monotonic() {
+        loom::model(|| {
+            let clock = Arc::new(AtomicHlc::new(HybridLogicalClock::new(10)).unwrap());
+            let other = Arc::clone(&clock);
+            let handle = thread::spawn(move || {
+                let first = other.tick(10).unwrap();
+                let second = other.tick(9).unwrap();
+                assert!(first < second);
+                second
+            });
+            let mine = clock.tick(11).unwrap();
+            let theirs = handle.join().unwrap();
+            assert_ne!(mine, theirs);
+            assert!(clock.load() >= mine && clock.load() >= theirs);
+        });
+    }
+
+    #[test]
+    fn concurrent_tick_and_update() {
+        loom::model(|| {
+            let clock = Arc::new(AtomicHlc::new(HybridLogicalClock::new(10)).unwrap());
+            let other = Arc::clone(&clock);
+            let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(20, 3);
+            let handle = thread::spawn(move || other.update(&received, 10).unwrap());
+            let ticked = clock.tick(10).unwrap();
+            let updated = handle.join().unwrap();
+            assert!(updated > received);
+            assert_ne!(ticked, updated);
+            assert_eq!(clock.load(), core::cmp::max(ticked, updated));
+        });
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{ClockError, HybridLogicalClock};
+
+impl HybridLogicalClock {
+    /// The length of the binary encoding produced by [`HybridLogicalClock::to_be_bytes`].
+    pub const ENCODED_LEN: usize = 12;
+
+    /// The length of the binary encoding produced by [`HybridLogicalClock::to_be_bytes_with_node`].
+    pub const ENCODED_LEN_WITH_NODE: usize = 20;
+
+    /// Encodes the clock as 12 big-endian bytes: the physical component followed by the
+    /// logical component.
+    ///
+    /// Comparing encodings byte by byte gives the same result as comparing the clocks,
+    /// so the encoding can be used as a prefix of storage keys.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let earlier = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(255, 9);
+    /// let later = HybridLogicalClock::new(256);
+    ///
+    /// assert!(earlier.to_be_bytes() < later.to_be_bytes());
+    /// assert_eq!(HybridLogicalClock::from_be_bytes(later.to_be_bytes()), later);
+    /// ```
+    pub fn to_be_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[..8].copy_from_slice(&self.physical.to_be_bytes());
+        bytes[8..].copy_from_slice(&self.logical.to_be_bytes());
+        bytes
+    }
+
+    /// Decodes a clock from the encoding produced by [`HybridLogicalClock::to_be_bytes`].
+    pub fn from_be_bytes(bytes: [u8; Self::ENCODED_LEN]) -> Self {
+        let (physical, logical) = bytes.split_at(8);
+        Self::new_with_both_physical_and_logical_clock_time(
+            u64::from_be_bytes(physical.try_into().unwrap()),
+            u32::from_be_bytes(logical.try_into().unwrap()),
+        )
+    }
+
+    /// Encodes the clock followed by a node id as 20 big-endian bytes.
+    ///
+    /// The encoding sorts by clock first and breaks ties by node id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let hlc = HybridLogicalClock::new(1000);
+    /// let bytes = hlc.to_be_bytes_with_node(7);
+    ///
+    /// assert!(bytes < hlc.to_be_bytes_with_node(8));
+    /// assert_eq!(HybridLogicalClock::from_be_bytes_with_node(bytes), (hlc, 7));
+    /// ```
+    pub fn to_be_bytes_with_node(&self, node: u64) -> [u8; Self::ENCODED_LEN_WITH_NODE] {
+        let mut bytes = [0; Self::ENCODED_LEN_WITH_NODE];
+        bytes[..Self::ENCODED_LEN].copy_from_slice(&self.to_be_bytes());
+        bytes[Self::ENCODED_LEN..].copy_from_slice(&node.to_be_bytes());
+        bytes
+    }
+
+    /// Decodes a clock and node id from the encoding produced by
+    /// [`HybridLogicalClock::to_be_bytes_with_node`].
+    pub fn from_be_bytes_with_node(bytes: [u8; Self::ENCODED_LEN_WITH_NODE]) -> (Self, u64) {
+        let (hlc, node) = bytes.split_at(Self::ENCODED_LEN);
+        (
+            Self::from_be_bytes(hlc.try_into().unwrap()),
+            u64::from_be_bytes(node.try_into().unwrap()),
+        )
+    }
+
+    /// Writes the encoding produced by [`HybridLogicalClock::to_be_bytes`] to the start of
+    /// `buf` without allocating.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written, or [`ClockError::BufferTooSmall`] if `buf` is shorter
+    /// than [`HybridLogicalClock::ENCODED_LEN`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let mut key = [0u8; 16];
+    /// let written = HybridLogicalClock::new(1000).write_to(&mut key).unwrap();
+    /// key[written..].copy_from_slice(b"user");
+    ///
+    /// assert_eq!(HybridLogicalClock::read_from(&key), Ok(HybridLogicalClock::new(1000)));
+    /// ```
+    pub fn write_to(&self, buf: &mut [u8]) -> Result<usize, ClockError> {
+        let actual = buf.len();
+        let buf = buf
+            .get_mut(..Self::ENCODED_LEN)
+            .ok_or(ClockError::BufferTooSmall {
+                needed: Self::ENCODED_LEN,
+                actual,
+            })?;
+        buf.copy_from_slice(&self.to_be_bytes());
+        Ok(Self::ENCODED_LEN)
+    }
+
+    /// Reads a clock from the start of `buf`, as written by [`HybridLogicalClock::write_to`].
+    ///
+    /// # Returns
+    ///
+    /// The decoded clock, or [`ClockError::BufferTooSmall`] if `buf` is shorter than
+    /// [`HybridLogicalClock::ENCODED_LEN`].
+    pub fn read_from(buf: &[u8]) -> Result<Self, ClockError> {
+        let bytes = buf
+            .get(..Self::ENCODED_LEN)
+            .ok_or(ClockError::BufferTooSmall {
+                needed: Self::ENCODED_LEN,
+                actual: buf.len(),
+            })?;
+        Ok(Self::from_be_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_layout() {
+        let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+            0x0102_0304_0506_0708,
+            0x090a_0b0c,
+        );
+        assert_eq!(hlc.to_be_bytes(), [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+        assert_eq!(
+            hlc.to_be_bytes_with_node(0x0d0e_0f10_1112_1314),
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20]
+        );
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        let mut buf = [0u8; 11];
+        assert_eq!(
+            HybridLogicalClock::new(1).write_to(&mut buf),
+            Err(ClockError::BufferTooSmall {
+                needed: 12,
+                actual: 11
+            })
+        );
+        assert_eq!(
+            HybridLogicalClock::read_from(&buf),
+            Err(ClockError::BufferTooSmall {
+                needed: 12,
+                actual: 11
+            })
+        );
+    }
+
+    fn clock() -> impl Strategy<Value = HybridLogicalClock> {
+        // Small values make equal physical components, and so logical tie-breaks, likely.
+        (
+            prop_oneof![0u64..4, any::<u64>()],
+            prop_oneof![0u32..4, any::<u32>()],
+        )
+            .prop_map(|(physical, logical)| {
+                HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_byte_order_matches_clock_order(a in clock(), b in clock()) {
+            prop_assert_eq!(a.cmp(&b), a.to_be_bytes().cmp(&b.to_be_bytes()));
+        }
+
+        #[test]
+        fn prop_byte_order_with_node_matches_clock_then_node_order(
+            a in clock(),
+            b in clock(),
+            node_a in 0u64..4,
+            node_b in 0u64..4,
+        ) {
+            prop_assert_eq!(
+                (a, node_a).cmp(&(b, node_b)),
+                a.to_be_bytes_with_node(node_a).cmp(&b.to_be_bytes_with_node(node_b))
+            );
+        }
+
+        #[test]
+        fn prop_round_trip(hlc in clock(), node in any::<u64>()) {
+            prop_assert_eq!(HybridLogicalClock::from_be_bytes(hlc.to_be_bytes()), hlc);
+            prop_assert_eq!(
+                HybridLogicalClock::from_be_bytes_with_node(hlc.to_be_bytes_with_node(node)),
+                (hlc, node)
+            );
+            let mut buf = [0u8; 32];
+            prop_assert_eq!(hlc.write_to(&mut buf), Ok(HybridLogicalClock::ENCODED_LEN));
+            prop_assert_eq!(HybridLogicalClock::read_from(&buf), Ok(hlc));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::vector::{HlcVector, VectorClock};
+use crate::{HybridLogicalClock, NodeId, Timestamp};
+
+/// A hybrid logical clock together with a version vector, which can detect concurrency.
+///
+/// Hybrid logical clock timestamps order every pair of events, including events that
+/// happened without knowledge of each other, so they cannot tell concurrent events apart
+/// from causally related ones. A `CausalContext` is an [`HlcVector`] keyed by [`NodeId`]
+/// that hands out [`Timestamp`]s. See [`HlcVector`] for how events are compared: the
+/// [`PartialEq`] and [`PartialOrd`] implementations compare histories, and `partial_cmp`
+/// returns `None` for concurrent contexts.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{CausalContext, NodeId};
+///
+/// let mut alice = CausalContext::new(NodeId(1), 1000);
+/// let mut bob = CausalContext::new(NodeId(2), 1000);
+///
+/// // Alice sends a message to Bob.
+/// alice.tick(1001);
+/// bob.receive(&alice, 1002);
+/// assert!(alice.happened_before(&bob));
+///
+/// // Both then write without talking to each other.
+/// alice.tick(1003);
+/// bob.tick(1003);
+/// assert!(alice.concurrent_with(&bob));
+/// assert_eq!(alice.partial_cmp(&bob), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd)]
+pub struct CausalContext(HlcVector<NodeId>);
+
+impl CausalContext {
+    /// Creates a new CausalContext for `node` with an empty history, starting its clock at
+    /// physical time `now`.
+    pub fn new(node: NodeId, now: u64) -> Self {
+        Self(HlcVector::new(node, now))
+    }
+
+    /// Records a local or send event and returns its timestamp. See [`HlcVector::tick`].
+    pub fn tick(&mut self, now: u64) -> Timestamp {
+        Timestamp::new(self.0.tick(now), self.node())
+    }
+
+    /// Records the receipt of a message carrying `received` and returns the timestamp of
+    /// the receive event. See [`HlcVector::receive`].
+    pub fn receive(&mut self, received: &CausalContext, now: u64) -> Timestamp {
+        Timestamp::new(self.0.receive(&received.0, now), self.node())
+    }
+
+    /// Returns true if this context happened before `other`.
+    pub fn happened_before(&self, other: &CausalContext) -> bool {
+        self.0.happened_before(&other.0)
+    }
+
+    /// Returns true if neither context happened before the other.
+    pub fn concurrent_with(&self, other: &CausalContext) -> bool {
+        self.0.concurrent_with(&other.0)
+    }
+
+    /// Returns the node this context belongs to.
+    pub fn node(&self) -> NodeId {
+        *self.0.node()
+    }
+
+    /// Returns the hybrid logical clock of the last recorded event.
+    pub fn hlc(&self) -> HybridLogicalClock {
+        self.0.hlc()
+    }
+
+    /// Returns the timestamp of the last recorded event.
+    pub fn timestamp(&self) -> Timestamp {
+        self.0.timestamp()
+    }
+
+    /// Returns how many events from `node` are in this context's history.
+    pub fn events_from(&self, node: NodeId) -> u64 {
+        self.0.events_from(&node)
+    }
+
+    /// Returns the version vector of this context's history.
+    pub fn vector(&self) -> &VectorClock<NodeId> {
+        self.0.vector()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cmp::Ordering;
+
+    #[test]
+    fn test_relations() {
+        let mut a = CausalContext::new(NodeId(1), 0);
+        let mut b = CausalContext::new(NodeId(2), 0);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+
+        a.tick(1);
+        assert!(b.happened_before(&a));
+        assert!(!a.happened_before(&b));
+
+        b.tick(1);
+        assert!(a.concurrent_with(&b));
+        assert!(b.concurrent_with(&a));
+        // The hybrid logical clocks are equal, yet the events are concurrent.
+        assert_eq!(a.hlc(), b.hlc());
+
+        let before = a.clone();
+        let received = a.receive(&b, 0);
+        assert!(before.happened_before(&a));
+        assert!(b.happened_before(&a));
+        assert!(received > b.timestamp());
+        assert_eq!(received, a.timestamp());
+        assert_eq!(a.events_from(NodeId(1)), 2);
+        assert_eq!(a.events_from(NodeId(2)), 1);
+    }
+}
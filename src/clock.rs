@@ -0,0 +1,474 @@
+use crate::{
+    check_offset, ClockConfig, ClockError, ClockStats, HybridLogicalClock, NodeId, OffsetPolicy,
+    OverflowPolicy, PhysicalClock, Timestamp,
+};
+
+/// A hybrid logical clock that reads its own physical time.
+///
+/// `Clock` pairs a [`HybridLogicalClock`] with a [`PhysicalClock`] so that call sites
+/// only deal with timestamps and never with raw wall clock readings.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{Clock, HybridLogicalClock, ManualClock};
+///
+/// let wall = ManualClock::new(1000);
+/// let mut clock = Clock::new(&wall);
+///
+/// let t1 = clock.tick();
+/// assert_eq!((t1.physical, t1.logical), (1000, 1));
+///
+/// wall.advance(10);
+/// let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1005, 3);
+/// let t2 = clock.update(&received);
+/// assert_eq!(t2.physical, 1010);
+/// assert!(t2 > t1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Clock<P> {
+    hlc: HybridLogicalClock,
+    physical_clock: P,
+    config: ClockConfig,
+    node: NodeId,
+    stats: ClockStats,
+}
+
+impl<P: PhysicalClock> Clock<P> {
+    /// Creates a new Clock starting at the current time of `physical_clock`.
+    pub fn new(physical_clock: P) -> Self {
+        let hlc = HybridLogicalClock::new(physical_clock.now());
+        Self {
+            hlc,
+            physical_clock,
+            config: ClockConfig::new(),
+            node: NodeId::default(),
+            stats: ClockStats::default(),
+        }
+    }
+
+    /// Creates a new Clock that resumes from a previously issued timestamp.
+    ///
+    /// Timestamps issued by the returned clock are always greater than `hlc`, even if
+    /// `physical_clock` reads an earlier time.
+    pub fn with_state(hlc: HybridLogicalClock, physical_clock: P) -> Self {
+        Self {
+            hlc,
+            physical_clock,
+            config: ClockConfig::new(),
+            node: NodeId::default(),
+            stats: ClockStats::default(),
+        }
+    }
+
+    /// Sets the configuration used by the clock.
+    ///
+    /// [`ClockConfig::max_logical`] bounds every operation. The offset guard and the
+    /// overflow and logical limit policies only apply to the checked operations
+    /// [`Clock::try_tick`] and [`Clock::try_update`].
+    pub fn with_config(mut self, config: ClockConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the id of the node this clock runs on, which [`Clock::next_timestamp`] attaches
+    /// to every timestamp. Defaults to `NodeId(0)`.
+    pub fn with_node(mut self, node: NodeId) -> Self {
+        self.node = node;
+        self
+    }
+
+    /// Advances the clock for a local or send event and returns the new timestamp.
+    ///
+    /// See [`HybridLogicalClock::tick`]. If the logical component would exceed
+    /// [`ClockConfig::max_logical`], the physical component is advanced by one unit instead.
+    /// Like [`HybridLogicalClock::tick`], it returns the current value again if the clock
+    /// cannot advance at all. Use [`Clock::try_tick`] to detect that.
+    pub fn tick(&mut self) -> HybridLogicalClock {
+        let now = self.physical_clock.now();
+        let next = self.hlc.send_rule(now);
+        self.advance(next, now, OverflowPolicy::BorrowPhysical)
+    }
+
+    /// Advances the clock for a local or send event and returns the new timestamp tagged
+    /// with this clock's node id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{Clock, ManualClock, NodeId};
+    ///
+    /// let mut clock = Clock::new(ManualClock::new(1000)).with_node(NodeId(7));
+    /// let timestamp = clock.next_timestamp();
+    ///
+    /// assert_eq!(timestamp.hlc(), clock.current());
+    /// assert_eq!(timestamp.node, NodeId(7));
+    /// ```
+    pub fn next_timestamp(&mut self) -> Timestamp {
+        Timestamp::new(self.tick(), self.node)
+    }
+
+    /// Advances the clock for a local or send event, applying the configured [`OverflowPolicy`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{Clock, ClockConfig, ClockError, ManualClock, OverflowPolicy};
+    ///
+    /// let config = ClockConfig::new().with_max_logical(1, OverflowPolicy::Reject);
+    /// let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+    ///
+    /// assert_eq!(clock.try_tick().unwrap().logical, 1);
+    /// assert!(matches!(clock.try_tick(), Err(ClockError::LogicalOverflow { .. })));
+    /// ```
+    pub fn try_tick(&mut self) -> Result<HybridLogicalClock, ClockError> {
+        let now = self.physical_clock.now();
+        let next = self.hlc.send_rule(now);
+        let hlc = self.hlc.advance(
+            next,
+            self.config.max_logical(),
+            self.config.overflow_policy(),
+        )?;
+        self.stats.record_event(hlc, now);
+        Ok(hlc)
+    }
+
+    /// Updates the clock based on a received timestamp and returns the new timestamp.
+    ///
+    /// See [`HybridLogicalClock::update`]. If the logical component would exceed
+    /// [`ClockConfig::max_logical`], the physical component is advanced by one unit instead.
+    pub fn update(&mut self, received: &HybridLogicalClock) -> HybridLogicalClock {
+        self.receive_from(received, None)
+    }
+
+    /// Updates the clock based on a timestamp received from another node, recording the
+    /// observed offset for that node in [`Clock::stats`].
+    ///
+    /// See [`Clock::update`].
+    pub fn update_from(&mut self, received: &Timestamp) -> HybridLogicalClock {
+        self.receive_from(&received.hlc(), Some(received.node))
+    }
+
+    /// Updates the clock based on a received timestamp, enforcing the configured limits.
+    ///
+    /// If the received physical time is more than [`ClockConfig::max_offset`] ahead of the
+    /// local physical time, the configured [`OffsetPolicy`] decides what happens. A received
+    /// logical component above [`ClockConfig::max_logical`] is always rejected, and the
+    /// configured [`OverflowPolicy`] applies if the local logical component would exceed it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{Clock, ClockConfig, ClockError, HybridLogicalClock, ManualClock, OffsetPolicy};
+    ///
+    /// let config = ClockConfig::new().with_max_offset(100, OffsetPolicy::Reject);
+    /// let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+    ///
+    /// let from_the_future = HybridLogicalClock::new(86_400_000);
+    /// assert!(matches!(
+    ///     clock.try_update(&from_the_future),
+    ///     Err(ClockError::OffsetExceeded { .. })
+    /// ));
+    /// assert_eq!(clock.current(), HybridLogicalClock::new(1000));
+    /// ```
+    pub fn try_update(
+        &mut self,
+        received: &HybridLogicalClock,
+    ) -> Result<HybridLogicalClock, ClockError> {
+        self.try_receive_from(received, None)
+    }
+
+    /// Updates the clock based on a timestamp received from another node, enforcing the
+    /// configured limits and recording the observed offset for that node in
+    /// [`Clock::stats`].
+    ///
+    /// See [`Clock::try_update`].
+    pub fn try_update_from(
+        &mut self,
+        received: &Timestamp,
+    ) -> Result<HybridLogicalClock, ClockError> {
+        self.try_receive_from(&received.hlc(), Some(received.node))
+    }
+
+    /// Returns what the clock has observed so far. Clone it to keep a snapshot.
+    pub fn stats(&self) -> &ClockStats {
+        &self.stats
+    }
+
+    /// Clears the statistics returned by [`Clock::stats`].
+    pub fn reset_stats(&mut self) {
+        self.stats = ClockStats::default();
+    }
+
+    fn try_receive_from(
+        &mut self,
+        received: &HybridLogicalClock,
+        peer: Option<NodeId>,
+    ) -> Result<HybridLogicalClock, ClockError> {
+        let max_logical = self.config.max_logical();
+        if received.logical > max_logical {
+            let error = ClockError::LogicalLimitExceeded {
+                received: received.logical,
+                max: max_logical,
+            };
+            self.stats.record_rejection(&error);
+            return Err(error);
+        }
+
+        let now = self.physical_clock.now();
+        let result = self.try_receive_at(received, now);
+        let peer = peer.filter(|_| result.is_ok());
+        self.stats
+            .record_offset(peer, received.physical, now, self.config.max_peers());
+        result
+    }
+
+    /// Applies the checks of [`Clock::try_update`] to `received` at the local time `now`.
+    fn try_receive_at(
+        &mut self,
+        received: &HybridLogicalClock,
+        now: u64,
+    ) -> Result<HybridLogicalClock, ClockError> {
+        let max_logical = self.config.max_logical();
+        let mut received = *received;
+        let mut report = None;
+        if let Some(max_offset) = self.config.max_offset() {
+            if let Err(error) = check_offset(&received, now, max_offset) {
+                match self.config.offset_policy() {
+                    OffsetPolicy::Reject => {
+                        self.stats.record_rejection(&error);
+                        return Err(error);
+                    }
+                    OffsetPolicy::Clamp => {
+                        received = HybridLogicalClock::new(now.saturating_add(max_offset));
+                    }
+                    OffsetPolicy::AcceptAndReport => report = Some(error),
+                }
+            }
+        }
+
+        let next = self.hlc.receive_rule(&received, now);
+        let updated = self
+            .hlc
+            .advance(next, max_logical, self.config.overflow_policy())
+            .inspect_err(|error| self.stats.record_rejection(error))?;
+        self.stats.record_event(updated, now);
+        if let Some(error) = report {
+            self.stats.record_offset_violation(error);
+        }
+        Ok(updated)
+    }
+
+    /// Returns the configuration used by the checked operations.
+    pub fn config(&self) -> &ClockConfig {
+        &self.config
+    }
+
+    /// Returns the id of the node this clock runs on.
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
+    /// Returns the last timestamp issued by the clock without advancing it.
+    pub fn current(&self) -> HybridLogicalClock {
+        self.hlc
+    }
+
+    /// Returns the physical clock this clock reads from.
+    pub fn physical_clock(&self) -> &P {
+        &self.physical_clock
+    }
+
+    /// Moves the clock forward to `floor` if it is behind it.
+    pub(crate) fn resume_from(&mut self, floor: HybridLogicalClock) {
+        self.hlc = self.hlc.max(floor);
+    }
+
+    fn receive_from(
+        &mut self,
+        received: &HybridLogicalClock,
+        peer: Option<NodeId>,
+    ) -> HybridLogicalClock {
+        let now = self.physical_clock.now();
+        self.stats
+            .record_offset(peer, received.physical, now, self.config.max_peers());
+        let next = self.hlc.receive_rule(received, now);
+        self.advance(next, now, OverflowPolicy::BorrowPhysical)
+    }
+
+    /// Moves the clock to `next` within the configured maximum logical value. If the clock
+    /// cannot move, it returns the current value again and records no event.
+    fn advance(
+        &mut self,
+        next: (u64, u64),
+        now: u64,
+        policy: OverflowPolicy,
+    ) -> HybridLogicalClock {
+        match self.hlc.advance(next, self.config.max_logical(), policy) {
+            Ok(hlc) => {
+                self.stats.record_event(hlc, now);
+                hlc
+            }
+            Err(_) => self.hlc,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ManualClock;
+
+    #[test]
+    fn test_tick_reads_physical_clock() {
+        let wall = ManualClock::new(100);
+        let mut clock = Clock::new(&wall);
+        assert_eq!(clock.current(), HybridLogicalClock::new(100));
+
+        wall.set(200);
+        assert_eq!(clock.tick(), HybridLogicalClock::new(200));
+
+        // The wall clock stepping backwards does not move the clock backwards.
+        wall.set(150);
+        let t = clock.tick();
+        assert_eq!((t.physical, t.logical), (200, 1));
+        assert_eq!(clock.current(), t);
+    }
+
+    #[test]
+    fn test_update_reads_physical_clock() {
+        let wall = ManualClock::new(100);
+        let mut clock = Clock::new(&wall);
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(300, 4);
+        let t = clock.update(&received);
+        assert_eq!((t.physical, t.logical), (300, 5));
+    }
+
+    #[test]
+    fn test_try_update_policies() {
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(5000, 9);
+
+        let config = ClockConfig::new().with_max_offset(100, OffsetPolicy::Reject);
+        let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+        assert!(matches!(
+            clock.try_update(&received),
+            Err(ClockError::OffsetExceeded {
+                received: 5000,
+                now: 1000,
+                max: 100
+            })
+        ));
+        assert_eq!(clock.current(), HybridLogicalClock::new(1000));
+
+        let config = ClockConfig::new().with_max_offset(100, OffsetPolicy::Clamp);
+        let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+        assert_eq!(
+            clock.try_update(&received),
+            Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1100, 1))
+        );
+
+        let config = ClockConfig::new().with_max_offset(100, OffsetPolicy::AcceptAndReport);
+        let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+        let absorbed = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(5000, 10);
+        assert_eq!(clock.try_update(&received), Ok(absorbed));
+        assert_eq!(clock.current(), absorbed);
+        assert_eq!(clock.stats().offset_violations(), 1);
+        assert_eq!(
+            clock.stats().last_offset_violation(),
+            Some(ClockError::OffsetExceeded {
+                received: 5000,
+                now: 1000,
+                max: 100
+            })
+        );
+        assert_eq!(clock.stats().rejected_updates(), 0);
+
+        // Without a maximum offset every timestamp is absorbed.
+        let mut clock = Clock::new(ManualClock::new(1000));
+        assert_eq!(clock.try_update(&received), Ok(clock.current()));
+        assert_eq!(clock.current().physical, 5000);
+    }
+
+    #[test]
+    fn test_max_logical() {
+        let wall = ManualClock::new(1000);
+        let config = ClockConfig::new().with_max_logical(2, OverflowPolicy::Reject);
+        let mut clock = Clock::new(&wall).with_config(config);
+
+        assert_eq!(clock.try_tick().map(|t| t.logical), Ok(1));
+        assert_eq!(clock.try_tick().map(|t| t.logical), Ok(2));
+        assert_eq!(
+            clock.try_tick(),
+            Err(ClockError::LogicalOverflow {
+                physical: 1000,
+                max: 2
+            })
+        );
+        // The unchecked operations borrow from the physical component instead.
+        assert_eq!(clock.tick(), HybridLogicalClock::new(1001));
+
+        // A remote timestamp above the limit is rejected without touching the clock.
+        let poisoned = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1001, 3);
+        assert_eq!(
+            clock.try_update(&poisoned),
+            Err(ClockError::LogicalLimitExceeded {
+                received: 3,
+                max: 2
+            })
+        );
+        assert_eq!(clock.current(), HybridLogicalClock::new(1001));
+        assert_eq!(clock.update(&poisoned), HybridLogicalClock::new(1002));
+    }
+
+    #[test]
+    fn test_stats() {
+        let wall = ManualClock::new(1000);
+        let config = ClockConfig::new().with_max_offset(100, OffsetPolicy::Reject);
+        let mut clock = Clock::new(&wall).with_config(config);
+
+        clock.tick();
+        clock.tick();
+        let ahead = Timestamp::new(HybridLogicalClock::new(1050), NodeId(1));
+        clock.update_from(&ahead);
+        // Rejected timestamps count towards the overall skew, but not towards their sender.
+        let broken = Timestamp::new(HybridLogicalClock::new(90_000), NodeId(2));
+        assert!(clock.try_update_from(&broken).is_err());
+        wall.set(2000);
+        clock.update(&HybridLogicalClock::new(1500));
+
+        let stats = clock.stats().clone();
+        assert_eq!(stats.events(), 4);
+        assert_eq!(stats.logical_bumps(), 3);
+        assert_eq!(stats.max_logical(), 2);
+        assert_eq!(stats.rejected_updates(), 1);
+        let skew = stats.skew().unwrap();
+        assert_eq!((skew.count(), skew.min(), skew.max()), (3, -500, 89_000));
+        #[cfg(feature = "alloc")]
+        {
+            assert_eq!(stats.peer_skew(&NodeId(1)).unwrap().last(), 50);
+            assert_eq!(stats.peer_skew(&NodeId(2)), None);
+        }
+
+        clock.reset_stats();
+        assert_eq!(clock.stats(), &ClockStats::default());
+    }
+
+    #[test]
+    fn test_stats_skip_events_that_do_not_advance() {
+        let last =
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(u64::MAX, u32::MAX);
+        let mut clock = Clock::with_state(last, ManualClock::new(1000));
+        assert_eq!(clock.tick(), last);
+        assert_eq!(clock.update(&last), last);
+        assert_eq!(clock.stats().events(), 0);
+        assert_eq!(clock.stats().skew().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_with_state() {
+        let resumed = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(500, 2);
+        let mut clock = Clock::with_state(resumed, ManualClock::new(10));
+        assert!(clock.tick() > resumed);
+        assert_eq!(clock.physical_clock().now(), 10);
+    }
+}
@@ -0,0 +1,136 @@
+/// What a [`Clock`](crate::Clock) does with a received timestamp that is too far ahead.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OffsetPolicy {
+    /// Leave the clock unchanged and return [`ClockError::OffsetExceeded`](crate::ClockError::OffsetExceeded).
+    #[default]
+    Reject,
+    /// Treat the received timestamp as if it were exactly the maximum offset ahead of the
+    /// local physical time. The clock keeps moving forward but does not absorb the bad time.
+    ///
+    /// The resulting timestamp may compare lower than the received one.
+    Clamp,
+    /// Absorb the received timestamp as [`Clock::update`](crate::Clock::update) would and
+    /// succeed, recording the violation in the clock's statistics so it can be reported.
+    /// See [`ClockStats::offset_violations`](crate::ClockStats::offset_violations).
+    AcceptAndReport,
+}
+
+/// What a [`Clock`](crate::Clock) does when its logical component would exceed
+/// [`ClockConfig::max_logical`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Advance the physical component by one unit and reset the logical component to 0.
+    ///
+    /// The clock stays strictly monotonic at the cost of running slightly ahead of the
+    /// physical time during a burst of events.
+    #[default]
+    BorrowPhysical,
+    /// Leave the clock unchanged and return [`ClockError::LogicalOverflow`](crate::ClockError::LogicalOverflow).
+    Reject,
+}
+
+/// Configuration for the checked operations of a [`Clock`](crate::Clock).
+///
+/// The default configuration accepts every received timestamp and lets the logical
+/// component use the full `u32` range, borrowing from the physical component on overflow.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{ClockConfig, OffsetPolicy};
+///
+/// use hybrid_logical_clock::OverflowPolicy;
+///
+/// let config = ClockConfig::new()
+///     .with_max_offset(500, OffsetPolicy::Reject)
+///     .with_max_logical(u16::MAX.into(), OverflowPolicy::BorrowPhysical);
+/// assert_eq!(config.max_offset(), Some(500));
+/// assert_eq!(config.offset_policy(), OffsetPolicy::Reject);
+/// assert_eq!(config.max_logical(), 65535);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockConfig {
+    max_offset: Option<u64>,
+    offset_policy: OffsetPolicy,
+    max_logical: u32,
+    overflow_policy: OverflowPolicy,
+    max_peers: usize,
+}
+
+impl ClockConfig {
+    /// The default number of peers whose offsets are tracked individually.
+    pub const DEFAULT_MAX_PEERS: usize = 64;
+
+    /// Creates a new ClockConfig that accepts every received timestamp.
+    pub const fn new() -> Self {
+        Self {
+            max_offset: None,
+            offset_policy: OffsetPolicy::Reject,
+            max_logical: u32::MAX,
+            overflow_policy: OverflowPolicy::BorrowPhysical,
+            max_peers: Self::DEFAULT_MAX_PEERS,
+        }
+    }
+
+    /// Sets the maximum tolerated offset (epsilon) between a received physical time and the
+    /// local physical time, and what to do when a received timestamp exceeds it.
+    pub const fn with_max_offset(mut self, max_offset: u64, policy: OffsetPolicy) -> Self {
+        self.max_offset = Some(max_offset);
+        self.offset_policy = policy;
+        self
+    }
+
+    /// Sets the largest logical component the clock will issue or accept, and what to do
+    /// when a local event would exceed it.
+    ///
+    /// Received timestamps with a larger logical component are always rejected with
+    /// [`ClockError::LogicalLimitExceeded`](crate::ClockError::LogicalLimitExceeded), so a
+    /// peer sending a value near the limit cannot exhaust the local counter.
+    pub const fn with_max_logical(mut self, max_logical: u32, policy: OverflowPolicy) -> Self {
+        self.max_logical = max_logical;
+        self.overflow_policy = policy;
+        self
+    }
+
+    /// Sets how many peers the clock tracks offsets for in its
+    /// [`ClockStats`](crate::ClockStats). Per-peer offsets need the `alloc` feature.
+    ///
+    /// Node ids come from the network, so the number of tracked peers is bounded. Once
+    /// `max_peers` peers are tracked, timestamps from other peers only count towards
+    /// [`ClockStats::skew`](crate::ClockStats::skew). Set it to 0 to track no peers.
+    pub const fn with_max_peers(mut self, max_peers: usize) -> Self {
+        self.max_peers = max_peers;
+        self
+    }
+
+    /// Returns the maximum tolerated offset, if any.
+    pub const fn max_offset(&self) -> Option<u64> {
+        self.max_offset
+    }
+
+    /// Returns the policy applied when the maximum offset is exceeded.
+    pub const fn offset_policy(&self) -> OffsetPolicy {
+        self.offset_policy
+    }
+
+    /// Returns the largest logical component the clock will issue or accept.
+    pub const fn max_logical(&self) -> u32 {
+        self.max_logical
+    }
+
+    /// Returns the policy applied when the logical component would exceed its maximum.
+    pub const fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+
+    /// Returns how many peers the clock tracks offsets for.
+    pub const fn max_peers(&self) -> usize {
+        self.max_peers
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,18 @@
+//! Conflict-free replicated data types ordered by [`Timestamp`](crate::Timestamp).
+//!
+//! Every write is tagged with the timestamp of the clock that made it, and merges keep the
+//! write with the larger timestamp. Because timestamps are unique per node and totally
+//! ordered, replicas that have seen the same writes hold the same state, whatever order the
+//! writes and merges arrived in.
+
+#[cfg(feature = "alloc")]
+mod map;
+mod register;
+#[cfg(feature = "alloc")]
+mod set;
+
+#[cfg(feature = "alloc")]
+pub use map::{Bias, LwwMap};
+pub use register::LwwRegister;
+#[cfg(feature = "alloc")]
+pub use set::LwwSet;
@@ -0,0 +1,460 @@
+use alloc::collections::BTreeMap;
+
+use crate::{Clock, HybridLogicalClock, PhysicalClock, Timestamp};
+
+/// Chooses whether an insert or a removal wins when both have the same clock value.
+///
+/// Timestamps from different nodes can share a clock value, in which case the node id
+/// normally decides. A bias decides between an insert and a removal first, so concurrent
+/// writes at the same instant resolve the same way whichever nodes made them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Bias {
+    /// Inserts win over removals with the same clock value.
+    #[default]
+    Add,
+    /// Removals win over inserts with the same clock value.
+    Remove,
+}
+
+/// The latest write to one key: a value, or a tombstone if the key was removed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Entry<V> {
+    value: Option<V>,
+    timestamp: Timestamp,
+}
+
+impl<V> Entry<V> {
+    /// Returns true if this write wins over `other`.
+    fn wins_over(&self, other: &Self, bias: Bias) -> bool {
+        let rank = |entry: &Self| {
+            let preferred = entry.value.is_some() == (bias == Bias::Add);
+            (entry.timestamp.hlc(), preferred, entry.timestamp.node)
+        };
+        rank(self) > rank(other)
+    }
+}
+
+/// A last-writer-wins map: every key holds the value of its latest write, and removals
+/// leave a tombstone so they win over older inserts.
+///
+/// Two maps are equal if they hold the same latest writes, tombstones included. The bias
+/// and the watermark of the last [`gc`](LwwMap::gc) are not compared.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::crdt::LwwMap;
+/// use hybrid_logical_clock::{Clock, ManualClock, NodeId};
+///
+/// let mut alice = Clock::new(ManualClock::new(1000)).with_node(NodeId(1));
+/// let mut bob = Clock::new(ManualClock::new(2000)).with_node(NodeId(2));
+///
+/// let mut a = LwwMap::new();
+/// a.insert("timeout", 30, &mut alice);
+/// a.insert("retries", 3, &mut alice);
+///
+/// let mut b = a.clone();
+/// b.remove(&"retries", &mut bob);
+/// a.insert("timeout", 60, &mut alice);
+///
+/// a.merge(&b);
+/// b.merge(&a);
+/// assert_eq!(a.get(&"timeout"), Some(&60));
+/// assert_eq!(a.get(&"retries"), None);
+/// assert!(a.iter().eq(b.iter()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LwwMap<K, V> {
+    entries: BTreeMap<K, Entry<V>>,
+    bias: Bias,
+    collected: Option<HybridLogicalClock>,
+}
+
+impl<K: Ord, V> LwwMap<K, V> {
+    /// Creates a new, empty LwwMap with [`Bias::Add`].
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            bias: Bias::Add,
+            collected: None,
+        }
+    }
+
+    /// Sets the bias used to break ties between inserts and removals.
+    ///
+    /// Every replica of the map must use the same bias.
+    pub fn with_bias(mut self, bias: Bias) -> Self {
+        self.bias = bias;
+        self
+    }
+
+    /// Returns the bias used to break ties between inserts and removals.
+    pub fn bias(&self) -> Bias {
+        self.bias
+    }
+
+    /// Returns the next timestamp of `clock` for a write to `key`, after absorbing the
+    /// timestamp of the key's latest write so the new write wins over it.
+    fn stamp<P: PhysicalClock>(&self, key: &K, clock: &mut Clock<P>) -> Timestamp {
+        match self.entries.get(key) {
+            Some(entry) => Timestamp::new(clock.update(&entry.timestamp.hlc()), clock.node()),
+            None => clock.next_timestamp(),
+        }
+    }
+
+    /// Sets `key` to `value`, stamped with the next timestamp of `clock`.
+    ///
+    /// # Returns
+    ///
+    /// The timestamp of the write.
+    pub fn insert<P: PhysicalClock>(
+        &mut self,
+        key: K,
+        value: V,
+        clock: &mut Clock<P>,
+    ) -> Timestamp {
+        let timestamp = self.stamp(&key, clock);
+        let entry = Entry {
+            value: Some(value),
+            timestamp,
+        };
+        self.entries.insert(key, entry);
+        timestamp
+    }
+
+    /// Removes `key`, leaving a tombstone stamped with the next timestamp of `clock`.
+    ///
+    /// # Returns
+    ///
+    /// The timestamp of the removal, or `None` if the key was not present, in which case
+    /// neither the map nor the clock changes.
+    pub fn remove<P: PhysicalClock>(&mut self, key: &K, clock: &mut Clock<P>) -> Option<Timestamp>
+    where
+        K: Clone,
+    {
+        if !self.contains_key(key) {
+            return None;
+        }
+        let timestamp = self.stamp(key, clock);
+        let entry = Entry {
+            value: None,
+            timestamp,
+        };
+        self.entries.insert(key.clone(), entry);
+        Some(timestamp)
+    }
+
+    /// Returns the value of `key`, if it is present.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key)?.value.as_ref()
+    }
+
+    /// Returns true if `key` is present.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the timestamp of the latest write to `key`, including removals that have
+    /// not been garbage collected yet.
+    pub fn timestamp(&self, key: &K) -> Option<Timestamp> {
+        self.entries.get(key).map(|entry| entry.timestamp)
+    }
+
+    /// Returns the present keys and values, in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, entry)| Some((key, entry.value.as_ref()?)))
+    }
+
+    /// Returns the number of present keys.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Returns true if no keys are present.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the number of tombstones kept for removed keys.
+    pub fn tombstones(&self) -> usize {
+        self.entries
+            .values()
+            .filter(|entry| entry.value.is_none())
+            .count()
+    }
+
+    /// Merges another replica of the map, or a delta from
+    /// [`delta_since`](LwwMap::delta_since), keeping the latest write to every key.
+    ///
+    /// Merging is commutative, associative and idempotent. Writes at or below the
+    /// watermark of the last [`gc`](LwwMap::gc) are ignored for keys without an entry,
+    /// since a removal of them has already been collected.
+    ///
+    /// # Returns
+    ///
+    /// The number of keys whose latest write changed.
+    pub fn merge(&mut self, other: &Self) -> usize
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut changed = 0;
+        for (key, incoming) in &other.entries {
+            match self.entries.get_mut(key) {
+                Some(entry) => {
+                    if incoming.wins_over(entry, self.bias) {
+                        *entry = incoming.clone();
+                        changed += 1;
+                    }
+                }
+                None => {
+                    if self.collected < Some(incoming.timestamp.hlc()) {
+                        self.entries.insert(key.clone(), incoming.clone());
+                        changed += 1;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    /// Returns a map holding only the writes made after `since`, including removals.
+    ///
+    /// Merging the delta into a replica that has every write up to `since` gives the same
+    /// result as merging the whole map.
+    pub fn delta_since(&self, since: HybridLogicalClock) -> Self
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Self {
+            entries: self
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.timestamp.hlc() > since)
+                .map(|(key, entry)| (key.clone(), entry.clone()))
+                .collect(),
+            bias: self.bias,
+            collected: None,
+        }
+    }
+
+    /// Drops the tombstones of removals at or below `stable`.
+    ///
+    /// `stable` must be a watermark every replica has seen all writes up to, such as the
+    /// one computed by a [`StabilityTracker`](crate::StabilityTracker). Otherwise a replica
+    /// that missed a collected removal can bring the removed value back.
+    ///
+    /// # Returns
+    ///
+    /// The number of tombstones dropped.
+    pub fn gc(&mut self, stable: HybridLogicalClock) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|_, entry| entry.value.is_some() || entry.timestamp.hlc() > stable);
+        self.collected = self.collected.max(Some(stable));
+        before - self.entries.len()
+    }
+}
+
+impl<K: Ord, V> Default for LwwMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for LwwMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for LwwMap<K, V> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ManualClock, NodeId};
+    use proptest::prelude::*;
+    use std::vec::Vec;
+
+    fn clock(node: u64) -> Clock<ManualClock> {
+        Clock::new(ManualClock::new(1000)).with_node(NodeId(node))
+    }
+
+    #[test]
+    fn test_bias() {
+        let hlc = HybridLogicalClock::new(1000);
+        let insert = Entry {
+            value: Some(1),
+            timestamp: Timestamp::new(hlc, NodeId(1)),
+        };
+        let remove = Entry {
+            value: None,
+            timestamp: Timestamp::new(hlc, NodeId(2)),
+        };
+        assert!(insert.wins_over(&remove, Bias::Add));
+        assert!(remove.wins_over(&insert, Bias::Remove));
+
+        let later = Entry {
+            value: None,
+            timestamp: Timestamp::new(HybridLogicalClock::new(1001), NodeId(0)),
+        };
+        assert!(later.wins_over(&insert, Bias::Add));
+    }
+
+    #[test]
+    fn test_remove_and_gc() {
+        let mut alice = clock(1);
+        let mut map = LwwMap::new();
+        assert_eq!(map.remove(&"a", &mut alice), None);
+
+        map.insert("a", 1, &mut alice);
+        map.insert("b", 2, &mut alice);
+        let stale = map.clone();
+        let removed = map.remove(&"a", &mut alice).unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.tombstones(), 1);
+        assert_eq!(map.timestamp(&"a"), Some(removed));
+
+        // Tombstones above the watermark are kept.
+        assert_eq!(map.gc(HybridLogicalClock::new(0)), 0);
+        assert_eq!(map.gc(removed.hlc()), 1);
+        assert_eq!(map.tombstones(), 0);
+        assert_eq!(map.timestamp(&"a"), None);
+
+        // A stale replica cannot bring the collected key back.
+        assert_eq!(map.merge(&stale), 0);
+        assert_eq!(map.iter().collect::<Vec<_>>(), [(&"b", &2)]);
+
+        // Newer writes are still accepted.
+        let mut bob = clock(2);
+        let mut other = LwwMap::new();
+        bob.update(&removed.hlc());
+        other.insert("a", 3, &mut bob);
+        assert_eq!(map.merge(&other), 1);
+        assert_eq!(map.get(&"a"), Some(&3));
+    }
+
+    #[test]
+    fn test_delta_since() {
+        let mut alice = clock(1);
+        let mut a = LwwMap::new();
+        a.insert(1, 'a', &mut alice);
+        a.insert(2, 'b', &mut alice);
+        let mut b = a.clone();
+        let since = alice.current();
+
+        alice.physical_clock().advance(5);
+        a.insert(3, 'c', &mut alice);
+        a.remove(&1, &mut alice);
+
+        let delta = a.delta_since(since);
+        assert_eq!(delta.entries.len(), 2);
+        b.merge(&delta);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_eq_ignores_the_gc_watermark() {
+        let mut alice = clock(1);
+        let mut a = LwwMap::new();
+        a.insert("a", 1, &mut alice);
+        let mut b = a.clone();
+        assert_eq!(b.gc(alice.current()), 0);
+        assert_eq!(a, b);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Step {
+        Insert { replica: usize, key: u8, value: u8 },
+        Remove { replica: usize, key: u8 },
+        Advance { replica: usize, millis: u64 },
+        Sync { from: usize, to: usize },
+    }
+
+    fn steps() -> impl Strategy<Value = Vec<Step>> {
+        proptest::collection::vec(
+            prop_oneof![
+                (0usize..3, 0u8..4, any::<u8>()).prop_map(|(replica, key, value)| Step::Insert {
+                    replica,
+                    key,
+                    value
+                }),
+                (0usize..3, 0u8..4).prop_map(|(replica, key)| Step::Remove { replica, key }),
+                (0usize..3, 0u64..3)
+                    .prop_map(|(replica, millis)| Step::Advance { replica, millis }),
+                (0usize..3, 0usize..3).prop_map(|(from, to)| Step::Sync { from, to }),
+            ],
+            0..40,
+        )
+    }
+
+    fn run(steps: &[Step], bias: Bias) -> Vec<LwwMap<u8, u8>> {
+        let mut clocks: Vec<_> = (0..3).map(clock).collect();
+        let mut replicas = std::vec![LwwMap::new().with_bias(bias); 3];
+        for step in steps {
+            match *step {
+                Step::Insert {
+                    replica,
+                    key,
+                    value,
+                } => {
+                    replicas[replica].insert(key, value, &mut clocks[replica]);
+                }
+                Step::Remove { replica, key } => {
+                    replicas[replica].remove(&key, &mut clocks[replica]);
+                }
+                Step::Advance { replica, millis } => {
+                    clocks[replica].physical_clock().advance(millis);
+                }
+                Step::Sync { from, to } => {
+                    let other = replicas[from].clone();
+                    replicas[to].merge(&other);
+                }
+            }
+        }
+        replicas
+    }
+
+    proptest! {
+        #[test]
+        fn prop_merge_converges(
+            steps in steps(),
+            order in Just((0..3).collect::<Vec<usize>>()).prop_shuffle(),
+            remove_bias in any::<bool>(),
+        ) {
+            let bias = if remove_bias { Bias::Remove } else { Bias::Add };
+            let replicas = run(&steps, bias);
+
+            // Merging every replica, in any order and with duplicates, gives the same map.
+            let mut forward = LwwMap::new().with_bias(bias);
+            for replica in &replicas {
+                forward.merge(replica);
+            }
+            let mut shuffled = replicas[order[0]].clone();
+            for &index in &order {
+                shuffled.merge(&replicas[index]);
+                shuffled.merge(&replicas[index]);
+            }
+            prop_assert_eq!(&forward.entries, &shuffled.entries);
+
+            // Collecting every tombstone once all replicas have converged keeps the contents.
+            let contents: Vec<_> = forward.iter().map(|(k, v)| (*k, *v)).collect();
+            let watermark = forward
+                .entries
+                .values()
+                .map(|entry| entry.timestamp.hlc())
+                .max()
+                .unwrap_or(HybridLogicalClock::new(0));
+            forward.gc(watermark);
+            prop_assert_eq!(forward.tombstones(), 0);
+            for replica in &replicas {
+                forward.merge(replica);
+            }
+            prop_assert!(forward.iter().map(|(k, v)| (*k, *v)).eq(contents));
+        }
+    }
+}
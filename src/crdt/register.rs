@@ -0,0 +1,171 @@
+use crate::{Clock, PhysicalClock, Timestamp};
+
+/// A last-writer-wins register: a value and the timestamp of the write that set it.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::crdt::LwwRegister;
+/// use hybrid_logical_clock::{Clock, ManualClock, NodeId};
+///
+/// let mut alice = Clock::new(ManualClock::new(1000)).with_node(NodeId(1));
+/// let mut bob = Clock::new(ManualClock::new(1000)).with_node(NodeId(2));
+///
+/// let mut a = LwwRegister::new("draft", alice.next_timestamp());
+/// let mut b = a.clone();
+/// a.set("alice", &mut alice);
+/// b.set("bob", &mut bob);
+///
+/// // Both writes have the same clock value, so the node id breaks the tie.
+/// let mut merged = a.clone();
+/// merged.merge(&b);
+/// b.merge(&a);
+/// assert_eq!(merged, b);
+/// assert_eq!(*merged.value(), "bob");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LwwRegister<T> {
+    value: T,
+    timestamp: Timestamp,
+}
+
+impl<T> LwwRegister<T> {
+    /// Creates a new LwwRegister holding `value`, written at `timestamp`.
+    pub fn new(value: T, timestamp: Timestamp) -> Self {
+        Self { value, timestamp }
+    }
+
+    /// Writes a new value, stamped with the next timestamp of `clock`.
+    ///
+    /// The clock first absorbs the register's current timestamp, so the new write always
+    /// wins over the value it replaces, even if that value came from a node whose clock is
+    /// ahead.
+    ///
+    /// # Returns
+    ///
+    /// The timestamp of the write.
+    pub fn set<P: PhysicalClock>(&mut self, value: T, clock: &mut Clock<P>) -> Timestamp {
+        let hlc = clock.update(&self.timestamp.hlc());
+        self.value = value;
+        self.timestamp = Timestamp::new(hlc, clock.node());
+        self.timestamp
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the timestamp of the write that set the current value.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the current value, consuming the register.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Merges another replica of the register, keeping the value with the larger
+    /// timestamp.
+    ///
+    /// Merging is commutative, associative and idempotent.
+    ///
+    /// # Returns
+    ///
+    /// True if the value was replaced.
+    pub fn merge(&mut self, other: &Self) -> bool
+    where
+        T: Clone,
+    {
+        if other.timestamp > self.timestamp {
+            self.value = other.value.clone();
+            self.timestamp = other.timestamp;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HybridLogicalClock, ManualClock, NodeId};
+    use proptest::prelude::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_set_wins_over_a_clock_ahead() {
+        let wall = ManualClock::new(1000);
+        let mut clock = Clock::new(&wall).with_node(NodeId(1));
+        let ahead = Timestamp::new(HybridLogicalClock::new(5000), NodeId(2));
+        let mut register = LwwRegister::new(0, ahead);
+
+        let written = register.set(1, &mut clock);
+        assert!(written > ahead);
+        assert_eq!(written.node, NodeId(1));
+        assert_eq!(register.timestamp(), written);
+        assert_eq!(clock.current(), written.hlc());
+
+        let mut stale = LwwRegister::new(2, ahead);
+        assert!(stale.merge(&register));
+        assert!(!register.merge(&stale));
+        assert_eq!(register.into_value(), 1);
+    }
+
+    fn writes() -> impl Strategy<Value = Vec<LwwRegister<u8>>> {
+        // Timestamps are unique per write, as they are when every node ticks its own clock.
+        proptest::collection::btree_map((0u64..4, 0u32..4, 0u64..4), any::<u8>(), 1..12).prop_map(
+            |writes| {
+                writes
+                    .into_iter()
+                    .map(|((physical, logical, node), value)| {
+                        let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                            physical, logical,
+                        );
+                        LwwRegister::new(value, Timestamp::new(hlc, NodeId(node)))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Generates writes and a delivery order that reorders and duplicates them.
+    fn deliveries() -> impl Strategy<Value = (Vec<LwwRegister<u8>>, Vec<usize>)> {
+        writes().prop_flat_map(|writes| {
+            let len = writes.len();
+            let order = proptest::collection::vec(0..len, 0..3 * len).prop_flat_map(move |extra| {
+                let mut order: Vec<usize> = (0..len).collect();
+                order.extend(extra);
+                Just(order).prop_shuffle()
+            });
+            (Just(writes), order)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_merge_converges((writes, order) in deliveries()) {
+            let latest = writes.iter().max_by_key(|register| register.timestamp()).unwrap();
+            let replay = |order: &[usize]| {
+                let mut replica = writes[order[0]].clone();
+                for &index in &order[1..] {
+                    replica.merge(&writes[index]);
+                }
+                replica
+            };
+            prop_assert_eq!(&replay(&order), latest);
+
+            // Merging whole replicas in either direction converges as well.
+            let (left, right) = order.split_at(order.len() / 2);
+            let mut a = if left.is_empty() { replay(right) } else { replay(left) };
+            let mut b = replay(right);
+            let a_before = a.clone();
+            a.merge(&b);
+            b.merge(&a_before);
+            prop_assert_eq!(&a, &b);
+            prop_assert_eq!(&a, latest);
+        }
+    }
+}
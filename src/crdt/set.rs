@@ -0,0 +1,225 @@
+use crate::crdt::{Bias, LwwMap};
+use crate::{Clock, HybridLogicalClock, PhysicalClock, Timestamp};
+
+/// A last-writer-wins element set: every element is present if its latest write was an
+/// insert.
+///
+/// This is an [`LwwMap`] from elements to `()`, with the same bias, delta and tombstone
+/// collection behavior.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::crdt::{Bias, LwwSet};
+/// use hybrid_logical_clock::{Clock, ManualClock, NodeId};
+///
+/// let mut clock = Clock::new(ManualClock::new(1000)).with_node(NodeId(1));
+/// let mut members = LwwSet::new().with_bias(Bias::Remove);
+///
+/// members.insert("alice", &mut clock);
+/// members.insert("bob", &mut clock);
+/// members.remove(&"alice", &mut clock);
+///
+/// assert!(members.iter().eq([&"bob"]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LwwSet<T> {
+    map: LwwMap<T, ()>,
+}
+
+impl<T: Ord> LwwSet<T> {
+    /// Creates a new, empty LwwSet with [`Bias::Add`].
+    pub fn new() -> Self {
+        Self { map: LwwMap::new() }
+    }
+
+    /// Sets the bias used to break ties between inserts and removals.
+    ///
+    /// Every replica of the set must use the same bias.
+    pub fn with_bias(self, bias: Bias) -> Self {
+        Self {
+            map: self.map.with_bias(bias),
+        }
+    }
+
+    /// Returns the bias used to break ties between inserts and removals.
+    pub fn bias(&self) -> Bias {
+        self.map.bias()
+    }
+
+    /// Inserts `element`, stamped with the next timestamp of `clock`.
+    ///
+    /// # Returns
+    ///
+    /// The timestamp of the write.
+    pub fn insert<P: PhysicalClock>(&mut self, element: T, clock: &mut Clock<P>) -> Timestamp {
+        self.map.insert(element, (), clock)
+    }
+
+    /// Removes `element`, leaving a tombstone stamped with the next timestamp of `clock`.
+    ///
+    /// # Returns
+    ///
+    /// The timestamp of the removal, or `None` if the element was not present.
+    pub fn remove<P: PhysicalClock>(
+        &mut self,
+        element: &T,
+        clock: &mut Clock<P>,
+    ) -> Option<Timestamp>
+    where
+        T: Clone,
+    {
+        self.map.remove(element, clock)
+    }
+
+    /// Returns true if `element` is present.
+    pub fn contains(&self, element: &T) -> bool {
+        self.map.contains_key(element)
+    }
+
+    /// Returns the timestamp of the latest write to `element`, including removals that
+    /// have not been garbage collected yet.
+    pub fn timestamp(&self, element: &T) -> Option<Timestamp> {
+        self.map.timestamp(element)
+    }
+
+    /// Returns the present elements, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.map.iter().map(|(element, _)| element)
+    }
+
+    /// Returns the number of present elements.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if no elements are present.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the number of tombstones kept for removed elements.
+    pub fn tombstones(&self) -> usize {
+        self.map.tombstones()
+    }
+
+    /// Merges another replica of the set, or a delta. See [`LwwMap::merge`].
+    ///
+    /// # Returns
+    ///
+    /// The number of elements whose latest write changed.
+    pub fn merge(&mut self, other: &Self) -> usize
+    where
+        T: Clone,
+    {
+        self.map.merge(&other.map)
+    }
+
+    /// Returns a set holding only the writes made after `since`. See
+    /// [`LwwMap::delta_since`].
+    pub fn delta_since(&self, since: HybridLogicalClock) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            map: self.map.delta_since(since),
+        }
+    }
+
+    /// Drops the tombstones of removals at or below `stable`. See [`LwwMap::gc`].
+    ///
+    /// # Returns
+    ///
+    /// The number of tombstones dropped.
+    pub fn gc(&mut self, stable: HybridLogicalClock) -> usize {
+        self.map.gc(stable)
+    }
+}
+
+impl<T: Ord> Default for LwwSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ManualClock, NodeId};
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_bias() {
+        for bias in [Bias::Add, Bias::Remove] {
+            let mut alice = Clock::new(ManualClock::new(1000)).with_node(NodeId(1));
+            let mut bob = Clock::new(ManualClock::new(1000)).with_node(NodeId(2));
+            let mut a = LwwSet::new().with_bias(bias);
+            let mut b = LwwSet::new().with_bias(bias);
+
+            a.insert("y", &mut alice);
+            let inserted = a.insert("x", &mut alice);
+            b.insert("x", &mut bob);
+            let removed = b.remove(&"x", &mut bob).unwrap();
+            // The writes tie on the clock value, and the bias decides before the node ids.
+            assert_eq!(inserted.hlc(), removed.hlc());
+
+            a.merge(&b);
+            b.merge(&a);
+            assert_eq!(a, b);
+            assert_eq!(a.contains(&"x"), bias == Bias::Add);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_insert_and_remove() {
+        let mut alice = Clock::new(ManualClock::new(1000)).with_node(NodeId(1));
+        let mut bob = Clock::new(ManualClock::new(1000)).with_node(NodeId(2));
+        let mut a = LwwSet::new().with_bias(Bias::Remove);
+        a.insert("x", &mut alice);
+        let mut b = a.clone();
+
+        // Without a tie, the later write wins whatever the bias.
+        alice.physical_clock().advance(1);
+        a.remove(&"x", &mut alice);
+        bob.physical_clock().advance(2);
+        b.insert("x", &mut bob);
+        assert!(!a.contains(&"x"));
+
+        a.merge(&b);
+        assert!(a.contains(&"x"));
+        b.merge(&a);
+        assert_eq!(a, b);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_merge_is_commutative(
+            ops in proptest::collection::vec((0usize..2, 0u8..4, any::<bool>(), 0u64..3), 0..40),
+            remove_bias in any::<bool>(),
+        ) {
+            let bias = if remove_bias { Bias::Remove } else { Bias::Add };
+            let mut clocks = [1, 2].map(|node| Clock::new(ManualClock::new(1000)).with_node(NodeId(node)));
+            let mut replicas = [LwwSet::new().with_bias(bias), LwwSet::new().with_bias(bias)];
+            for (replica, element, insert, millis) in ops {
+                let clock = &mut clocks[replica];
+                clock.physical_clock().advance(millis);
+                if insert {
+                    replicas[replica].insert(element, clock);
+                } else {
+                    replicas[replica].remove(&element, clock);
+                }
+            }
+
+            let [a, b] = replicas;
+            let mut ab = a.clone();
+            ab.merge(&b);
+            let mut ba = b.clone();
+            ba.merge(&a);
+            prop_assert_eq!(&ab, &ba);
+
+            let mut abab = ab.clone();
+            prop_assert_eq!(abab.merge(&ab), 0);
+            prop_assert_eq!(&abab, &ab);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+//! Dotted version vectors, for tracking concurrent values per key in a replicated store.
+//!
+//! This follows Almeida et al., "Scalable and Accurate Causality Tracking for Eventually
+//! Consistent Stores". Every stored value is tagged with a [`Dvv`]: a [`Dot`] naming the
+//! write that created it, and the version vector of everything the writer had seen. A
+//! replica keeps the [`Siblings`] of a key, the values that no other stored value
+//! supersedes. Each dot also carries the [`HybridLogicalClock`] of the write, so clients can
+//! still show when a sibling was written and order siblings for display.
+//!
+//! A client reads the siblings and their context (see [`Siblings::join`]), and sends that
+//! context back with its next write. The write then replaces exactly the siblings the
+//! client had seen, while concurrent writes survive as new siblings.
+
+use alloc::vec::Vec;
+
+use crate::vector::VectorClock;
+use crate::{HybridLogicalClock, NodeId};
+
+/// A single write event: the `counter`-th write coordinated by `node`.
+///
+/// Dots are identified by node and counter. The timestamp records when the write happened
+/// and is not part of the identity.
+#[derive(Debug, Clone, Copy)]
+pub struct Dot<N = NodeId> {
+    /// The node that coordinated the write.
+    pub node: N,
+    /// The number of writes `node` has coordinated, including this one.
+    pub counter: u64,
+    /// The hybrid logical clock of the write.
+    pub timestamp: HybridLogicalClock,
+}
+
+impl<N: PartialEq> PartialEq for Dot<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node && self.counter == other.counter
+    }
+}
+
+impl<N: Eq> Eq for Dot<N> {}
+
+/// A dotted version vector: the dot of a write and the causal past it was made in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dvv<N = NodeId> {
+    dot: Dot<N>,
+    past: VectorClock<N>,
+}
+
+impl<N: Ord + Clone> Dvv<N> {
+    /// Returns the dot of the write.
+    pub fn dot(&self) -> &Dot<N> {
+        &self.dot
+    }
+
+    /// Returns the version vector of everything the writer had seen.
+    pub fn past(&self) -> &VectorClock<N> {
+        &self.past
+    }
+
+    /// Returns true if the write is part of the history described by `context`.
+    pub fn is_covered_by(&self, context: &VectorClock<N>) -> bool {
+        self.dot.counter <= context.get(&self.dot.node)
+    }
+
+    /// Returns true if this write happened before `other`, that is if `other` was made
+    /// with knowledge of it.
+    pub fn happened_before(&self, other: &Self) -> bool {
+        self.is_covered_by(&other.past)
+    }
+
+    /// Returns true if neither write happened before the other.
+    pub fn concurrent_with(&self, other: &Self) -> bool {
+        self != other && !self.happened_before(other) && !other.happened_before(self)
+    }
+}
+
+/// The concurrent values stored for one key, each tagged with a [`Dvv`].
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::dvv::Siblings;
+/// use hybrid_logical_clock::vector::VectorClock;
+/// use hybrid_logical_clock::{HybridLogicalClock, NodeId};
+///
+/// let mut replica = Siblings::new();
+/// let empty = VectorClock::new();
+///
+/// // Two clients write without reading first, so both values are kept.
+/// replica.put(&empty, NodeId(1), "red", HybridLogicalClock::new(1000));
+/// replica.put(&empty, NodeId(1), "blue", HybridLogicalClock::new(1001));
+/// assert_eq!(replica.len(), 2);
+///
+/// // A third client reads both and writes a resolution with the context it read.
+/// let context = replica.join();
+/// replica.put(&context, NodeId(1), "purple", HybridLogicalClock::new(1002));
+/// assert_eq!(replica.values().collect::<Vec<_>>(), [&"purple"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Siblings<V, N = NodeId> {
+    versions: Vec<(Dvv<N>, V)>,
+    /// Every write this replica has stored or synced, including discarded ones.
+    version: VectorClock<N>,
+}
+
+impl<V, N: Ord + Clone> Siblings<V, N> {
+    /// Creates a new Siblings with no values.
+    pub fn new() -> Self {
+        Self {
+            versions: Vec::new(),
+            version: VectorClock::new(),
+        }
+    }
+
+    /// Returns the version vector summarizing every value, the context a client should
+    /// send back with its next write.
+    pub fn join(&self) -> VectorClock<N> {
+        let mut context = VectorClock::new();
+        for (dvv, _) in &self.versions {
+            context.merge(&dvv.past);
+            context.observe(dvv.dot.node.clone(), dvv.dot.counter);
+        }
+        context
+    }
+
+    /// Removes the values whose writes are part of the history described by `context`.
+    pub fn discard(&mut self, context: &VectorClock<N>) {
+        self.versions.retain(|(dvv, _)| !dvv.is_covered_by(context));
+    }
+
+    /// Creates the dotted version vector for a new write coordinated by `node`, made with
+    /// knowledge of `context`, at `timestamp`.
+    ///
+    /// The dot's counter is one more than any write by `node` in `context` or that this
+    /// replica has ever stored or synced, so it is never reused, even after the key's
+    /// values were discarded.
+    pub fn event(
+        &self,
+        context: &VectorClock<N>,
+        node: N,
+        timestamp: HybridLogicalClock,
+    ) -> Dvv<N> {
+        let counter = context.get(&node).max(self.version.get(&node)) + 1;
+        Dvv {
+            dot: Dot {
+                node,
+                counter,
+                timestamp,
+            },
+            past: context.clone(),
+        }
+    }
+
+    /// Stores a new value written with knowledge of `context`, coordinated by `node`.
+    ///
+    /// The values the writer had seen are discarded and concurrent values are kept.
+    ///
+    /// # Returns
+    ///
+    /// The dotted version vector of the new value.
+    pub fn put(
+        &mut self,
+        context: &VectorClock<N>,
+        node: N,
+        value: V,
+        timestamp: HybridLogicalClock,
+    ) -> Dvv<N> {
+        let dvv = self.event(context, node, timestamp);
+        self.discard(context);
+        self.version.merge(context);
+        self.version.observe(dvv.dot.node.clone(), dvv.dot.counter);
+        self.versions.push((dvv.clone(), value));
+        dvv
+    }
+
+    /// Merges the values of another replica of the same key, keeping only the values
+    /// that no value in either replica supersedes.
+    ///
+    /// Values with the same dot are the same write, because a node never reuses a counter
+    /// for a key.
+    pub fn sync(&mut self, other: &Self)
+    where
+        V: Clone,
+    {
+        self.version.merge(&other.version);
+        for (dvv, value) in &other.versions {
+            let known = self
+                .versions
+                .iter()
+                .any(|(existing, _)| existing.dot == dvv.dot || dvv.happened_before(existing));
+            if !known {
+                self.versions
+                    .retain(|(existing, _)| !existing.happened_before(dvv));
+                self.versions.push((dvv.clone(), value.clone()));
+            }
+        }
+    }
+
+    /// Returns the stored values.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.versions.iter().map(|(_, value)| value)
+    }
+
+    /// Returns the stored values with their dotted version vectors.
+    pub fn iter(&self) -> impl Iterator<Item = (&Dvv<N>, &V)> {
+        self.versions.iter().map(|(dvv, value)| (dvv, value))
+    }
+
+    /// Returns the number of stored values.
+    pub fn len(&self) -> usize {
+        self.versions.len()
+    }
+
+    /// Returns true if no values are stored.
+    pub fn is_empty(&self) -> bool {
+        self.versions.is_empty()
+    }
+}
+
+impl<V, N: Ord + Clone> Default for Siblings<V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn at(physical: u64) -> HybridLogicalClock {
+        HybridLogicalClock::new(physical)
+    }
+
+    #[test]
+    fn test_put_and_sync() {
+        let empty = VectorClock::new();
+        let mut a = Siblings::new();
+        let mut b = Siblings::new();
+
+        let first = a.put(&empty, NodeId(1), "first", at(10));
+        assert_eq!(first.dot().counter, 1);
+        assert_eq!(first.dot().timestamp, at(10));
+
+        b.sync(&a);
+        let context = b.join();
+        let second = b.put(&context, NodeId(2), "second", at(20));
+        assert!(first.happened_before(&second));
+
+        // A concurrent write on the first replica.
+        let third = a.put(&a.join(), NodeId(1), "third", at(15));
+        assert!(third.concurrent_with(&second));
+
+        a.sync(&b);
+        b.sync(&a);
+        let mut values_a: Vec<_> = a.values().copied().collect();
+        let mut values_b: Vec<_> = b.values().copied().collect();
+        values_a.sort();
+        values_b.sort();
+        assert_eq!(values_a, ["second", "third"]);
+        assert_eq!(values_a, values_b);
+
+        // Syncing again changes nothing.
+        let before = a.len();
+        a.sync(&b);
+        assert_eq!(a.len(), before);
+
+        // A client that read both resolves the conflict on either replica.
+        a.put(&a.join(), NodeId(1), "resolved", at(30));
+        b.sync(&a);
+        assert_eq!(b.values().collect::<Vec<_>>(), [&"resolved"]);
+    }
+
+    #[test]
+    fn test_dots_are_never_reused() {
+        let mut replica = Siblings::new();
+        let empty = VectorClock::new();
+        let a = replica.put(&empty, NodeId(1), 'a', at(0));
+        let b = replica.put(&empty, NodeId(1), 'b', at(0));
+        assert_ne!(a.dot(), b.dot());
+
+        replica.discard(&replica.join());
+        assert!(replica.is_empty());
+        // The counter continues from the context, not from the now empty set of values.
+        let context: VectorClock = [(NodeId(1), 2)].into_iter().collect();
+        assert_eq!(
+            replica.put(&context, NodeId(1), 'c', at(0)).dot().counter,
+            3
+        );
+    }
+
+    #[test]
+    fn test_emptied_key_keeps_counting() {
+        let empty = VectorClock::new();
+        let mut a = Siblings::new();
+        let mut b = Siblings::new();
+        let first = a.put(&empty, NodeId(1), "first", at(10));
+        b.sync(&a);
+
+        // Emptying the key does not forget the writes it has seen.
+        a.discard(&a.join());
+        assert!(a.is_empty());
+        let second = a.put(&empty, NodeId(1), "second", at(20));
+        assert_eq!(second.dot().counter, 2);
+        assert!(second.concurrent_with(&first));
+
+        // A replica that still has the first value keeps both.
+        b.sync(&a);
+        let mut values: Vec<_> = b.values().copied().collect();
+        values.sort();
+        assert_eq!(values, ["first", "second"]);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Step {
+        /// A client reads from a replica and remembers the context in a slot.
+        Get { replica: usize, slot: usize },
+        /// A client writes to a replica with the context from a slot, or with none.
+        Put { replica: usize, slot: Option<usize> },
+        /// One replica pulls the values of another.
+        Sync { from: usize, to: usize },
+    }
+
+    fn steps() -> impl Strategy<Value = Vec<Step>> {
+        proptest::collection::vec(
+            prop_oneof![
+                (0usize..3, 0usize..3).prop_map(|(replica, slot)| Step::Get { replica, slot }),
+                (0usize..3, proptest::option::of(0usize..3))
+                    .prop_map(|(replica, slot)| Step::Put { replica, slot }),
+                (0usize..3, 0usize..3).prop_map(|(from, to)| Step::Sync { from, to }),
+            ],
+            0..40,
+        )
+    }
+
+    /// The reference model: every value is identified by the id of the write that created
+    /// it, and carries the explicit set of writes in its causal history, itself included.
+    type Histories = BTreeMap<u64, BTreeSet<u64>>;
+
+    fn reference_sync(into: &mut Histories, from: &Histories) {
+        let mut all = into.clone();
+        all.extend(from.iter().map(|(id, history)| (*id, history.clone())));
+        *into = all
+            .iter()
+            .filter(|(_, history)| {
+                !all.values()
+                    .any(|other| history.is_subset(other) && history != &other)
+            })
+            .map(|(id, history)| (*id, history.clone()))
+            .collect();
+    }
+
+    proptest! {
+        /// Runs random reads, writes and anti-entropy against both the dotted version
+        /// vectors and a model with explicit causal histories, and checks that every
+        /// replica keeps exactly the values the model says are not superseded.
+        #[test]
+        fn prop_matches_causal_histories(steps in steps()) {
+            let mut replicas: Vec<Siblings<u64>> = std::vec![Siblings::new(); 3];
+            let mut models: Vec<Histories> = std::vec![Histories::new(); 3];
+            let mut slots: Vec<(VectorClock, BTreeSet<u64>)> =
+                std::vec![(VectorClock::new(), BTreeSet::new()); 3];
+            let mut next_id = 0;
+
+            for step in steps {
+                match step {
+                    Step::Get { replica, slot } => {
+                        let history = models[replica].values().flatten().copied().collect();
+                        slots[slot] = (replicas[replica].join(), history);
+                    }
+                    Step::Put { replica, slot } => {
+                        let (context, history) = match slot {
+                            Some(slot) => slots[slot].clone(),
+                            None => (VectorClock::new(), BTreeSet::new()),
+                        };
+                        let id = next_id;
+                        next_id += 1;
+                        replicas[replica].put(&context, NodeId(replica as u64), id, at(id));
+
+                        let mut own = history.clone();
+                        own.insert(id);
+                        models[replica].retain(|existing, _| !history.contains(existing));
+                        models[replica].insert(id, own);
+                    }
+                    Step::Sync { from, to } => {
+                        let other = replicas[from].clone();
+                        replicas[to].sync(&other);
+                        let other = models[from].clone();
+                        reference_sync(&mut models[to], &other);
+                    }
+                }
+
+                for (replica, model) in replicas.iter().zip(&models) {
+                    let values: BTreeSet<u64> = replica.values().copied().collect();
+                    prop_assert_eq!(values.len(), replica.len(), "duplicate values");
+                    prop_assert_eq!(&values, &model.keys().copied().collect());
+                }
+            }
+        }
+
+        #[test]
+        fn prop_sync_is_commutative_and_idempotent(steps in steps()) {
+            let mut replicas: Vec<Siblings<u64>> = std::vec![Siblings::new(); 3];
+            let mut next_id = 0;
+            for step in steps {
+                match step {
+                    Step::Get { .. } => {}
+                    Step::Put { replica, slot } => {
+                        let context = match slot {
+                            Some(source) => replicas[source].join(),
+                            None => VectorClock::new(),
+                        };
+                        replicas[replica].put(&context, NodeId(replica as u64), next_id, at(next_id));
+                        next_id += 1;
+                    }
+                    Step::Sync { from, to } => {
+                        let other = replicas[from].clone();
+                        replicas[to].sync(&other);
+                    }
+                }
+            }
+
+            let values = |siblings: &Siblings<u64>| siblings.values().copied().collect::<BTreeSet<_>>();
+            let mut ab = replicas[0].clone();
+            ab.sync(&replicas[1]);
+            let mut ba = replicas[1].clone();
+            ba.sync(&replicas[0]);
+            prop_assert_eq!(values(&ab), values(&ba));
+            prop_assert_eq!(ab.join(), ba.join());
+
+            let mut abab = ab.clone();
+            abab.sync(&ab);
+            prop_assert_eq!(values(&abab), values(&ab));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use core::fmt;
+
+/// Errors returned by the fallible clock operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockError {
+    /// A received physical time was further ahead of the local physical time than the
+    /// configured maximum offset allows.
+    OffsetExceeded {
+        /// The received physical time.
+        received: u64,
+        /// The local physical time when the timestamp was received.
+        now: u64,
+        /// The maximum tolerated offset.
+        max: u64,
+    },
+    /// The logical component would exceed its maximum value within one physical time.
+    LogicalOverflow {
+        /// The physical time at which the logical component overflowed.
+        physical: u64,
+        /// The maximum logical value.
+        max: u32,
+    },
+    /// A physical time was too large for the packed representation of a timestamp, or was
+    /// not a whole number of the representation's [`TimeUnit`](crate::TimeUnit).
+    PhysicalOutOfRange {
+        /// The physical time.
+        physical: u64,
+        /// The largest representable physical time.
+        max: u64,
+    },
+    /// A logical component was too large for the packed representation of a timestamp.
+    LogicalOutOfRange {
+        /// The logical component.
+        logical: u32,
+        /// The largest representable logical component.
+        max: u32,
+    },
+    /// A buffer was too short to hold an encoded timestamp.
+    BufferTooSmall {
+        /// The number of bytes required.
+        needed: usize,
+        /// The length of the buffer.
+        actual: usize,
+    },
+    /// Encoded bytes were malformed.
+    InvalidEncoding,
+    /// A received logical component was larger than the configured maximum.
+    LogicalLimitExceeded {
+        /// The received logical component.
+        received: u32,
+        /// The maximum logical value.
+        max: u32,
+    },
+    /// An interval tree clock stamp could not record an event because its event count
+    /// would exceed `u64::MAX`.
+    EventCountOverflow,
+}
+
+impl fmt::Display for ClockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClockError::OffsetExceeded { received, now, max } => write!(
+                f,
+                "received physical time {received} is more than {max} ahead of local physical time {now}"
+            ),
+            ClockError::LogicalOverflow { physical, max } => write!(
+                f,
+                "logical component would exceed {max} at physical time {physical}"
+            ),
+            ClockError::PhysicalOutOfRange { physical, max } => write!(
+                f,
+                "physical time {physical} exceeds the representable maximum of {max}"
+            ),
+            ClockError::LogicalOutOfRange { logical, max } => write!(
+                f,
+                "logical component {logical} exceeds the representable maximum of {max}"
+            ),
+            ClockError::BufferTooSmall { needed, actual } => write!(
+                f,
+                "buffer of {actual} bytes is too small, {needed} bytes are needed"
+            ),
+            ClockError::InvalidEncoding => f.write_str("invalid encoding"),
+            ClockError::LogicalLimitExceeded { received, max } => write!(
+                f,
+                "received logical component {received} exceeds the maximum of {max}"
+            ),
+            ClockError::EventCountOverflow => f.write_str("event count would exceed u64::MAX"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ClockError {}
+
+/// Errors returned when parsing a timestamp from text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The text does not have the shape of the expected format.
+    InvalidFormat,
+    /// A date or time field is outside its calendar range, such as February 30.
+    InvalidDate,
+    /// The value is well formed but cannot be represented, such as a time before the Unix
+    /// epoch or a logical component above `u32::MAX`.
+    OutOfRange,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidFormat => f.write_str("invalid timestamp format"),
+            ParseError::InvalidDate => f.write_str("invalid date or time in timestamp"),
+            ParseError::OutOfRange => f.write_str("timestamp out of range"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Errors returned by a [`PersistentClock`](crate::PersistentClock).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PersistError<E> {
+    /// The clock store failed to load or store the high-water mark.
+    Store(E),
+    /// The clock operation itself failed.
+    Clock(ClockError),
+}
+
+impl<E> From<ClockError> for PersistError<E> {
+    fn from(error: ClockError) -> Self {
+        PersistError::Clock(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for PersistError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Store(error) => write!(f, "clock store failed: {error}"),
+            PersistError::Clock(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for PersistError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PersistError::Store(error) => Some(error),
+            PersistError::Clock(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,315 @@
+//! Text formats for [`HybridLogicalClock`].
+//!
+//! The canonical format is the physical component as an RFC 3339 UTC time with
+//! millisecond precision, followed by the logical component zero-padded to at least four
+//! digits, for example `2026-10-18T12:00:00.123Z-0005`. The compact format is 24 lowercase
+//! hex digits, the hex form of [`HybridLogicalClock::to_be_bytes`].
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::{HybridLogicalClock, ParseError};
+
+const MILLIS_PER_SECOND: u64 = 1000;
+const MILLIS_PER_DAY: u64 = 86_400 * MILLIS_PER_SECOND;
+
+impl fmt::Display for HybridLogicalClock {
+    /// Formats the clock in the canonical text format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1_792_324_800_123, 5);
+    /// assert_eq!(hlc.to_string(), "2026-10-18T12:00:00.123Z-0005");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (year, month, day) = civil_from_days(self.physical / MILLIS_PER_DAY);
+        let millis_of_day = self.physical % MILLIS_PER_DAY;
+        let seconds_of_day = millis_of_day / MILLIS_PER_SECOND;
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z-{:04}",
+            year,
+            month,
+            day,
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60,
+            seconds_of_day % 60,
+            millis_of_day % MILLIS_PER_SECOND,
+            self.logical
+        )
+    }
+}
+
+impl fmt::LowerHex for HybridLogicalClock {
+    /// Formats the clock in the compact hex format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 5);
+    /// assert_eq!(format!("{hlc:x}"), "00000000000003e800000005");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}{:08x}", self.physical, self.logical)
+    }
+}
+
+impl FromStr for HybridLogicalClock {
+    type Err = ParseError;
+
+    /// Parses the canonical text format produced by [`Display`](fmt::Display).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let hlc: HybridLogicalClock = "2026-10-18T12:00:00.123Z-0005".parse().unwrap();
+    /// assert_eq!(hlc.physical, 1_792_324_800_123);
+    /// assert_eq!(hlc.logical, 5);
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (time, logical) = s.split_once("Z-").ok_or(ParseError::InvalidFormat)?;
+        let physical = parse_time(time)?;
+        if logical.len() < 4 {
+            return Err(ParseError::InvalidFormat);
+        }
+        let logical = parse_digits(logical)?;
+        let logical = u32::try_from(logical).map_err(|_| ParseError::OutOfRange)?;
+        Ok(Self::new_with_both_physical_and_logical_clock_time(
+            physical, logical,
+        ))
+    }
+}
+
+impl HybridLogicalClock {
+    /// Parses the compact hex format produced by [`LowerHex`](fmt::LowerHex).
+    ///
+    /// Both lowercase and uppercase digits are accepted, but the string must be exactly 24
+    /// digits long.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 5);
+    /// assert_eq!(HybridLogicalClock::from_hex(&format!("{hlc:x}")), Ok(hlc));
+    /// ```
+    pub fn from_hex(s: &str) -> Result<Self, ParseError> {
+        if s.len() != 24 || !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseError::InvalidFormat);
+        }
+        let (physical, logical) = s.split_at(16);
+        // Both halves are known to be valid hex of the right length.
+        Ok(Self::new_with_both_physical_and_logical_clock_time(
+            u64::from_str_radix(physical, 16).map_err(|_| ParseError::InvalidFormat)?,
+            u32::from_str_radix(logical, 16).map_err(|_| ParseError::InvalidFormat)?,
+        ))
+    }
+}
+
+/// Parses an unsigned decimal number made only of ASCII digits.
+pub(crate) fn parse_digits(s: &str) -> Result<u64, ParseError> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::InvalidFormat);
+    }
+    s.parse().map_err(|_| ParseError::OutOfRange)
+}
+
+/// Parses `YYYY-MM-DDTHH:MM:SS.mmm` (without the trailing `Z`) into milliseconds since the
+/// Unix epoch.
+fn parse_time(s: &str) -> Result<u64, ParseError> {
+    let (date, time) = s.split_once('T').ok_or(ParseError::InvalidFormat)?;
+
+    let mut date_fields = date.splitn(3, '-');
+    let year = date_fields.next().ok_or(ParseError::InvalidFormat)?;
+    let month = fixed_width(date_fields.next(), 2)?;
+    let day = fixed_width(date_fields.next(), 2)?;
+    if year.len() < 4 {
+        return Err(ParseError::InvalidFormat);
+    }
+    let year = parse_digits(year)?;
+
+    let (time, millis) = time.split_once('.').ok_or(ParseError::InvalidFormat)?;
+    let millis = fixed_width(Some(millis), 3)?;
+    let mut time_fields = time.splitn(3, ':');
+    let hour = fixed_width(time_fields.next(), 2)?;
+    let minute = fixed_width(time_fields.next(), 2)?;
+    let second = fixed_width(time_fields.next(), 2)?;
+
+    if year < 1970 {
+        return Err(ParseError::OutOfRange);
+    }
+    if !(1..=12).contains(&month)
+        || day == 0
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(ParseError::InvalidDate);
+    }
+
+    let seconds_of_day = hour * 3600 + minute * 60 + second;
+    days_from_civil(year, month, day)
+        .and_then(|days| days.checked_mul(MILLIS_PER_DAY))
+        .and_then(|millis_at_midnight| {
+            millis_at_midnight.checked_add(seconds_of_day * MILLIS_PER_SECOND + millis)
+        })
+        .ok_or(ParseError::OutOfRange)
+}
+
+fn fixed_width(field: Option<&str>, width: usize) -> Result<u64, ParseError> {
+    match field {
+        Some(field) if field.len() == width => parse_digits(field),
+        _ => Err(ParseError::InvalidFormat),
+    }
+}
+
+fn is_leap_year(year: u64) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u64, month: u64) -> u64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The two conversions below are Howard Hinnant's `days_from_civil` and `civil_from_days`,
+// restricted to dates on or after the Unix epoch.
+
+/// Returns the number of days from 1970-01-01 to the given date, which must not be earlier.
+fn days_from_civil(year: u64, month: u64, day: u64) -> Option<u64> {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146_097)?
+        .checked_add(day_of_era)?
+        .checked_sub(719_468)
+}
+
+/// Returns the year, month and day that is `days` days after 1970-01-01.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::format;
+    use std::string::ToString;
+
+    fn hlc(physical: u64, logical: u32) -> HybridLogicalClock {
+        HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical)
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(hlc(0, 0).to_string(), "1970-01-01T00:00:00.000Z-0000");
+        assert_eq!(
+            hlc(951_868_799_999, 12_345).to_string(),
+            "2000-02-29T23:59:59.999Z-12345"
+        );
+        assert_eq!(
+            hlc(1_792_324_800_123, 5).to_string(),
+            "2026-10-18T12:00:00.123Z-0005"
+        );
+        assert_eq!(
+            format!("{:x}", hlc(u64::MAX, u32::MAX)),
+            "ffffffffffffffffffffffff"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (input, error) in [
+            ("", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00.123Z", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00.123Z-05", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00.123Z-+005", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00Z-0005", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00.12Z-0005", ParseError::InvalidFormat),
+            ("2026-10-18 12:00:00.123Z-0005", ParseError::InvalidFormat),
+            ("26-10-18T12:00:00.123Z-0005", ParseError::InvalidFormat),
+            ("2026-1-18T12:00:00.123Z-0005", ParseError::InvalidFormat),
+            (
+                "2026-10-18T12:00:00.123+00:00-0005",
+                ParseError::InvalidFormat,
+            ),
+            ("2026-02-29T12:00:00.123Z-0005", ParseError::InvalidDate),
+            ("2026-13-01T12:00:00.123Z-0005", ParseError::InvalidDate),
+            ("2026-10-18T24:00:00.000Z-0005", ParseError::InvalidDate),
+            ("2026-10-18T12:00:60.000Z-0005", ParseError::InvalidDate),
+            ("1969-12-31T23:59:59.999Z-0000", ParseError::OutOfRange),
+            (
+                "2026-10-18T12:00:00.123Z-4294967296",
+                ParseError::OutOfRange,
+            ),
+            ("584556020-01-01T00:00:00.000Z-0000", ParseError::OutOfRange),
+        ] {
+            assert_eq!(
+                input.parse::<HybridLogicalClock>(),
+                Err(error),
+                "parsing {input:?}"
+            );
+        }
+
+        assert_eq!(
+            HybridLogicalClock::from_hex("00000000000003E800000005"),
+            Ok(hlc(1000, 5))
+        );
+        assert_eq!(
+            HybridLogicalClock::from_hex("00000000000003e80000005"),
+            Err(ParseError::InvalidFormat)
+        );
+        assert_eq!(
+            HybridLogicalClock::from_hex("+0000000000003e800000005"),
+            Err(ParseError::InvalidFormat)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(physical in any::<u64>(), logical in any::<u32>()) {
+            let hlc = hlc(physical, logical);
+            prop_assert_eq!(hlc.to_string().parse::<HybridLogicalClock>(), Ok(hlc));
+            prop_assert_eq!(HybridLogicalClock::from_hex(&format!("{hlc:x}")), Ok(hlc));
+        }
+
+        #[test]
+        fn prop_hex_order_matches_clock_order(
+            a in (any::<u64>(), any::<u32>()),
+            b in (any::<u64>(), any::<u32>()),
+        ) {
+            let (a, b) = (hlc(a.0, a.1), hlc(b.0, b.1));
+            prop_assert_eq!(a.cmp(&b), format!("{a:x}").cmp(&format!("{b:x}")));
+        }
+    }
+}
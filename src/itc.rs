@@ -0,0 +1,874 @@
+//! Interval tree clocks, for causality tracking with dynamic membership.
+//!
+//! This follows Almeida, Baquero and Fonte, "Interval Tree Clocks: A Logical Clock for
+//! Dynamic Systems". Instead of indexing counts by node id, every participant owns a
+//! disjoint part of the unit interval and records events over the part it owns. New
+//! participants [`fork`](Stamp::fork) an existing stamp, and retiring ones
+//! [`join`](Stamp::join) theirs back, so the clock grows and shrinks with the number of
+//! active participants rather than with every node that ever existed.
+//!
+//! A [`Stamp`] can also carry a [`HybridLogicalClock`], so events still have a physical
+//! time and a total order.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::{ClockError, HybridLogicalClock};
+
+/// The part of the unit interval a stamp owns.
+///
+/// Ids are kept normalized: a node never has two `Zero` or two `One` children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Id {
+    Zero,
+    One,
+    Node(Box<Id>, Box<Id>),
+}
+
+impl Id {
+    /// Builds a node from its halves, normalizing it.
+    fn node(left: Id, right: Id) -> Id {
+        match (left, right) {
+            (Id::Zero, Id::Zero) => Id::Zero,
+            (Id::One, Id::One) => Id::One,
+            (left, right) => Id::Node(Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// Splits the id into two disjoint ids whose sum is the original.
+    fn split(&self) -> (Id, Id) {
+        match self {
+            Id::Zero => (Id::Zero, Id::Zero),
+            Id::One => (Id::node(Id::One, Id::Zero), Id::node(Id::Zero, Id::One)),
+            Id::Node(left, right) => match (&**left, &**right) {
+                (Id::Zero, right) => {
+                    let (first, second) = right.split();
+                    (Id::node(Id::Zero, first), Id::node(Id::Zero, second))
+                }
+                (left, Id::Zero) => {
+                    let (first, second) = left.split();
+                    (Id::node(first, Id::Zero), Id::node(second, Id::Zero))
+                }
+                (left, right) => (
+                    Id::node(left.clone(), Id::Zero),
+                    Id::node(Id::Zero, right.clone()),
+                ),
+            },
+        }
+    }
+
+    /// Returns the union of two ids.
+    fn sum(self, other: Id) -> Id {
+        match (self, other) {
+            (Id::Zero, id) | (id, Id::Zero) => id,
+            (Id::One, _) | (_, Id::One) => Id::One,
+            (Id::Node(left1, right1), Id::Node(left2, right2)) => {
+                Id::node(left1.sum(*left2), right1.sum(*right2))
+            }
+        }
+    }
+}
+
+/// The events recorded over the unit interval, as a tree of counts relative to the parent.
+///
+/// Event trees are kept normalized: the children of a node never are equal leaves, and at
+/// least one of them has a base of 0. The base of a normalized tree is its minimum.
+///
+/// Every count, added up along the path from the root, fits in a `u64`:
+/// [`Stamp::from_bytes`] rejects trees where it does not, and [`Stamp::event`] fails
+/// rather than grow a tree whose maximum is `u64::MAX`. `node`, `max`, `lift` and `join`
+/// only produce counts that one of their inputs already has, so they cannot overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+    Leaf(u64),
+    Node(u64, Box<Event>, Box<Event>),
+}
+
+/// The cost of expanding a leaf while growing, which makes growing prefer existing nodes.
+const EXPAND_COST: u64 = 1 << 32;
+
+impl Event {
+    /// Builds a node from its base and children, normalizing it.
+    fn node(base: u64, left: Event, right: Event) -> Event {
+        match (left, right) {
+            (Event::Leaf(left), Event::Leaf(right)) if left == right => Event::Leaf(base + left),
+            (left, right) => {
+                let min = left.base().min(right.base());
+                Event::Node(
+                    base + min,
+                    Box::new(left.sink(min)),
+                    Box::new(right.sink(min)),
+                )
+            }
+        }
+    }
+
+    fn base(&self) -> u64 {
+        match self {
+            Event::Leaf(base) | Event::Node(base, _, _) => *base,
+        }
+    }
+
+    fn max(&self) -> u64 {
+        match self {
+            Event::Leaf(base) => *base,
+            Event::Node(base, left, right) => base + left.max().max(right.max()),
+        }
+    }
+
+    fn lift(self, amount: u64) -> Event {
+        match self {
+            Event::Leaf(base) => Event::Leaf(base + amount),
+            Event::Node(base, left, right) => Event::Node(base + amount, left, right),
+        }
+    }
+
+    fn sink(self, amount: u64) -> Event {
+        match self {
+            Event::Leaf(base) => Event::Leaf(base - amount),
+            Event::Node(base, left, right) => Event::Node(base - amount, left, right),
+        }
+    }
+
+    /// Returns the least upper bound of two event trees.
+    fn join(self, other: Event) -> Event {
+        match (self, other) {
+            (Event::Leaf(first), Event::Leaf(second)) => Event::Leaf(first.max(second)),
+            (Event::Leaf(base), other) => {
+                Event::Node(base, Box::new(Event::Leaf(0)), Box::new(Event::Leaf(0))).join(other)
+            }
+            (this, Event::Leaf(base)) => this.join(Event::Node(
+                base,
+                Box::new(Event::Leaf(0)),
+                Box::new(Event::Leaf(0)),
+            )),
+            (Event::Node(base1, left1, right1), Event::Node(base2, left2, right2)) => {
+                if base1 > base2 {
+                    return Event::Node(base2, left2, right2)
+                        .join(Event::Node(base1, left1, right1));
+                }
+                let lift = base2 - base1;
+                Event::node(
+                    base1,
+                    left1.join(left2.lift(lift)),
+                    right1.join(right2.lift(lift)),
+                )
+            }
+        }
+    }
+
+    /// Returns true if this tree, lifted by `offset`, is at most `other` lifted by
+    /// `other_offset` everywhere.
+    fn leq(&self, offset: u64, other: &Event, other_offset: u64) -> bool {
+        match (self, other) {
+            (Event::Leaf(base), other) => base + offset <= other.base() + other_offset,
+            (Event::Node(base, left, right), Event::Leaf(_)) => {
+                let offset = base + offset;
+                offset <= other.base() + other_offset
+                    && left.leq(offset, other, other_offset)
+                    && right.leq(offset, other, other_offset)
+            }
+            (Event::Node(base, left1, right1), Event::Node(other_base, left2, right2)) => {
+                let offset = base + offset;
+                let other_offset = other_base + other_offset;
+                offset <= other_offset
+                    && left1.leq(offset, left2, other_offset)
+                    && right1.leq(offset, right2, other_offset)
+            }
+        }
+    }
+
+    /// Raises the tree over the owned part of the interval as far as possible without
+    /// inventing events, simplifying it.
+    fn fill(&self, id: &Id) -> Event {
+        match (id, self) {
+            (Id::Zero, _) => self.clone(),
+            (Id::One, _) => Event::Leaf(self.max()),
+            (_, Event::Leaf(_)) => self.clone(),
+            (Id::Node(left_id, right_id), Event::Node(base, left, right)) => {
+                match (&**left_id, &**right_id) {
+                    (Id::One, right_id) => {
+                        let right = right.fill(right_id);
+                        let left = Event::Leaf(left.max().max(right.base()));
+                        Event::node(*base, left, right)
+                    }
+                    (left_id, Id::One) => {
+                        let left = left.fill(left_id);
+                        let right = Event::Leaf(right.max().max(left.base()));
+                        Event::node(*base, left, right)
+                    }
+                    (left_id, right_id) => {
+                        Event::node(*base, left.fill(left_id), right.fill(right_id))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Records an event by incrementing the tree over the owned part of the interval,
+    /// choosing the change that keeps the tree smallest.
+    ///
+    /// # Returns
+    ///
+    /// The grown tree and the cost of the change.
+    fn grow(&self, id: &Id) -> (Event, u64) {
+        match (id, self) {
+            (Id::One, Event::Leaf(base)) => (Event::Leaf(base + 1), 0),
+            (Id::One, _) => (Event::Leaf(self.max() + 1), 0),
+            (_, Event::Leaf(base)) => {
+                let expanded =
+                    Event::Node(*base, Box::new(Event::Leaf(0)), Box::new(Event::Leaf(0)));
+                let (grown, cost) = expanded.grow(id);
+                (grown, cost.saturating_add(EXPAND_COST))
+            }
+            (Id::Node(left_id, right_id), Event::Node(base, left, right)) => {
+                let grow_left = || {
+                    let (grown, cost) = left.grow(left_id);
+                    (Event::node(*base, grown, (**right).clone()), cost + 1)
+                };
+                let grow_right = || {
+                    let (grown, cost) = right.grow(right_id);
+                    (Event::node(*base, (**left).clone(), grown), cost + 1)
+                };
+                match (&**left_id, &**right_id) {
+                    (Id::Zero, _) => grow_right(),
+                    (_, Id::Zero) => grow_left(),
+                    _ => {
+                        let (left, left_cost) = grow_left();
+                        let (right, right_cost) = grow_right();
+                        if left_cost < right_cost {
+                            (left, left_cost)
+                        } else {
+                            (right, right_cost)
+                        }
+                    }
+                }
+            }
+            (Id::Zero, _) => unreachable!("cannot record an event without an id"),
+        }
+    }
+}
+
+/// An interval tree clock stamp: the part of the interval a participant owns, the events
+/// it knows about, and optionally a hybrid logical clock.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::itc::Stamp;
+///
+/// let (mut alice, mut bob) = Stamp::seed().fork();
+/// alice.event().unwrap();
+/// bob.event().unwrap();
+/// assert!(alice.concurrent_with(&bob));
+///
+/// // Bob receives a message from Alice.
+/// bob.receive_at(&alice.peek(), 1000).unwrap();
+/// assert!(alice.happened_before(&bob));
+///
+/// // Alice retires and hands her part of the interval back.
+/// let merged = bob.join(alice);
+/// assert_eq!(merged, Stamp::seed().join(merged.peek()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stamp {
+    id: Id,
+    event: Event,
+    hlc: Option<HybridLogicalClock>,
+}
+
+impl Stamp {
+    /// The nesting depth [`Stamp::from_bytes`] accepts, which bounds its recursion.
+    const MAX_DEPTH: usize = 256;
+
+    /// Creates the seed stamp, which owns the whole interval and has seen no events.
+    ///
+    /// Every other stamp of a system is forked from one seed.
+    pub fn seed() -> Self {
+        Self {
+            id: Id::One,
+            event: Event::Leaf(0),
+            hlc: None,
+        }
+    }
+
+    /// Attaches a hybrid logical clock to the stamp.
+    pub fn with_hlc(mut self, hlc: HybridLogicalClock) -> Self {
+        self.hlc = Some(hlc);
+        self
+    }
+
+    /// Returns the stamp's hybrid logical clock, if it has one.
+    pub fn hlc(&self) -> Option<HybridLogicalClock> {
+        self.hlc
+    }
+
+    /// Returns true if the stamp owns no part of the interval, like the stamps returned by
+    /// [`Stamp::peek`]. Anonymous stamps can be joined but cannot record events.
+    pub fn is_anonymous(&self) -> bool {
+        self.id == Id::Zero
+    }
+
+    /// Splits the stamp into two with disjoint ids and the same history, for example to
+    /// hand one to a new participant.
+    pub fn fork(self) -> (Self, Self) {
+        let (first, second) = self.id.split();
+        let copy = Self {
+            id: second,
+            event: self.event.clone(),
+            hlc: self.hlc,
+        };
+        (Self { id: first, ..self }, copy)
+    }
+
+    /// Merges two stamps, combining their ids and histories. The hybrid logical clock of
+    /// the result is the larger of the two.
+    ///
+    /// Use it with another participant's stamp to take over its part of the interval when
+    /// it retires. Joining records no event and does not apply the hybrid logical clock
+    /// receive rule, so to receive a message use [`Stamp::receive_at`] instead, or follow
+    /// the join with [`Stamp::event_at`].
+    pub fn join(self, other: Self) -> Self {
+        Self {
+            id: self.id.sum(other.id),
+            event: self.event.join(other.event),
+            hlc: self.hlc.max(other.hlc),
+        }
+    }
+
+    /// Returns an anonymous copy of the stamp's history, to send along with a message.
+    pub fn peek(&self) -> Self {
+        Self {
+            id: Id::Zero,
+            event: self.event.clone(),
+            hlc: self.hlc,
+        }
+    }
+
+    /// Records an event.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())`, or [`ClockError::EventCountOverflow`] if recording the event would take a
+    /// count past `u64::MAX`, in which case the stamp is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stamp is anonymous.
+    pub fn event(&mut self) -> Result<(), ClockError> {
+        assert!(
+            !self.is_anonymous(),
+            "anonymous stamps cannot record events"
+        );
+        let filled = self.event.fill(&self.id);
+        if filled != self.event {
+            self.event = filled;
+            return Ok(());
+        }
+        // Growing raises a single count by one, so no count can exceed the new maximum.
+        self.event
+            .max()
+            .checked_add(1)
+            .ok_or(ClockError::EventCountOverflow)?;
+        self.event = self.event.grow(&self.id).0;
+        Ok(())
+    }
+
+    /// Records an event and ticks the stamp's hybrid logical clock, starting one at `now`
+    /// if the stamp has none.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current physical time
+    ///
+    /// # Returns
+    ///
+    /// The hybrid logical clock of the event, or [`ClockError::EventCountOverflow`] if the
+    /// event could not be recorded, in which case the stamp is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stamp is anonymous.
+    pub fn event_at(&mut self, now: u64) -> Result<HybridLogicalClock, ClockError> {
+        self.event()?;
+        let hlc = match self.hlc {
+            Some(mut hlc) => hlc.tick(now),
+            None => HybridLogicalClock::new(now),
+        };
+        self.hlc = Some(hlc);
+        Ok(hlc)
+    }
+
+    /// Receives a message: merges the history of the stamp sent with it, records the
+    /// receive event, and updates the stamp's hybrid logical clock with the receive rule
+    /// of [`HybridLogicalClock::update`].
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The stamp sent with the message, usually a [`peek`](Stamp::peek) of
+    ///   the sender's stamp. Only its history and clock are used.
+    /// * `now` - The current physical time
+    ///
+    /// # Returns
+    ///
+    /// The hybrid logical clock of the receive event, or
+    /// [`ClockError::EventCountOverflow`] if the event could not be recorded, in which case
+    /// the stamp is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stamp is anonymous.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::itc::Stamp;
+    ///
+    /// let (mut alice, mut bob) = Stamp::seed().fork();
+    /// let sent = alice.event_at(1000).unwrap();
+    ///
+    /// // Bob's physical clock is behind Alice's.
+    /// let received = bob.receive_at(&alice.peek(), 900).unwrap();
+    /// assert!(received > sent);
+    /// assert!(alice.happened_before(&bob));
+    /// ```
+    pub fn receive_at(
+        &mut self,
+        message: &Self,
+        now: u64,
+    ) -> Result<HybridLogicalClock, ClockError> {
+        let mut received = self.clone().join(message.peek());
+        received.event()?;
+        let hlc = match (self.hlc, message.hlc) {
+            (local, Some(remote)) => {
+                let mut local = local.unwrap_or(HybridLogicalClock::new(0));
+                local.update(&remote, now)
+            }
+            (Some(mut local), None) => local.tick(now),
+            (None, None) => HybridLogicalClock::new(now),
+        };
+        received.hlc = Some(hlc);
+        *self = received;
+        Ok(hlc)
+    }
+
+    /// Returns true if every event this stamp has seen is known to `other` as well.
+    pub fn leq(&self, other: &Self) -> bool {
+        self.event.leq(0, &other.event, 0)
+    }
+
+    /// Returns true if this stamp's history is strictly contained in `other`'s.
+    pub fn happened_before(&self, other: &Self) -> bool {
+        self.leq(other) && !other.leq(self)
+    }
+
+    /// Returns true if neither stamp's history contains the other's.
+    pub fn concurrent_with(&self, other: &Self) -> bool {
+        !self.leq(other) && !other.leq(self)
+    }
+
+    /// Encodes the stamp as its id tree, its event tree with LEB128 counts, and a flag
+    /// byte followed by the 12-byte hybrid logical clock if it has one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::itc::Stamp;
+    ///
+    /// let mut stamp = Stamp::seed();
+    /// stamp.event_at(1000).unwrap();
+    ///
+    /// assert_eq!(Stamp::from_bytes(&stamp.to_bytes()), Ok(stamp));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_id(&self.id, &mut bytes);
+        write_event(&self.event, &mut bytes);
+        match self.hlc {
+            Some(hlc) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&hlc.to_be_bytes());
+            }
+            None => bytes.push(0),
+        }
+        bytes
+    }
+
+    /// Decodes a stamp from the encoding produced by [`Stamp::to_bytes`].
+    ///
+    /// # Returns
+    ///
+    /// The decoded stamp, [`ClockError::BufferTooSmall`] if `bytes` is truncated, or
+    /// [`ClockError::InvalidEncoding`] if it has trailing bytes, unknown tags, trees that
+    /// are not normalized or nested too deeply, or counts that overflow.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ClockError> {
+        let mut reader = Reader { bytes, position: 0 };
+        let id = reader.id(0)?;
+        let event = reader.event(0, 0)?;
+        let hlc = match reader.byte()? {
+            0 => None,
+            1 => {
+                let encoded = reader.take(HybridLogicalClock::ENCODED_LEN)?;
+                Some(HybridLogicalClock::read_from(encoded)?)
+            }
+            _ => return Err(ClockError::InvalidEncoding),
+        };
+        if reader.position != bytes.len() {
+            return Err(ClockError::InvalidEncoding);
+        }
+        Ok(Self { id, event, hlc })
+    }
+}
+
+impl Default for Stamp {
+    fn default() -> Self {
+        Self::seed()
+    }
+}
+
+const ID_ZERO: u8 = 0;
+const ID_ONE: u8 = 1;
+const ID_NODE: u8 = 2;
+const EVENT_LEAF: u8 = 0;
+const EVENT_NODE: u8 = 1;
+
+fn write_id(id: &Id, bytes: &mut Vec<u8>) {
+    match id {
+        Id::Zero => bytes.push(ID_ZERO),
+        Id::One => bytes.push(ID_ONE),
+        Id::Node(left, right) => {
+            bytes.push(ID_NODE);
+            write_id(left, bytes);
+            write_id(right, bytes);
+        }
+    }
+}
+
+fn write_event(event: &Event, bytes: &mut Vec<u8>) {
+    match event {
+        Event::Leaf(base) => {
+            bytes.push(EVENT_LEAF);
+            write_varint(*base, bytes);
+        }
+        Event::Node(base, left, right) => {
+            bytes.push(EVENT_NODE);
+            write_varint(*base, bytes);
+            write_event(left, bytes);
+            write_event(right, bytes);
+        }
+    }
+}
+
+fn write_varint(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ClockError> {
+        let needed = self.position + len;
+        let taken = self
+            .bytes
+            .get(self.position..needed)
+            .ok_or(ClockError::BufferTooSmall {
+                needed,
+                actual: self.bytes.len(),
+            })?;
+        self.position = needed;
+        Ok(taken)
+    }
+
+    fn byte(&mut self) -> Result<u8, ClockError> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads a canonical LEB128 value: at most ten bytes, without trailing zero groups.
+    fn varint(&mut self) -> Result<u64, ClockError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let group = u64::from(byte & 0x7f);
+            if group << shift >> shift != group || (shift > 0 && byte == 0) {
+                return Err(ClockError::InvalidEncoding);
+            }
+            value |= group << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ClockError::InvalidEncoding)
+    }
+
+    fn id(&mut self, depth: usize) -> Result<Id, ClockError> {
+        if depth > Stamp::MAX_DEPTH {
+            return Err(ClockError::InvalidEncoding);
+        }
+        match self.byte()? {
+            ID_ZERO => Ok(Id::Zero),
+            ID_ONE => Ok(Id::One),
+            ID_NODE => match (self.id(depth + 1)?, self.id(depth + 1)?) {
+                (Id::Zero, Id::Zero) | (Id::One, Id::One) => Err(ClockError::InvalidEncoding),
+                (left, right) => Ok(Id::Node(Box::new(left), Box::new(right))),
+            },
+            _ => Err(ClockError::InvalidEncoding),
+        }
+    }
+
+    /// Reads an event tree whose ancestors add up to `offset`, rejecting trees whose
+    /// counts overflow.
+    fn event(&mut self, depth: usize, offset: u64) -> Result<Event, ClockError> {
+        if depth > Stamp::MAX_DEPTH {
+            return Err(ClockError::InvalidEncoding);
+        }
+        let tag = self.byte()?;
+        let base = self.varint()?;
+        let total = offset
+            .checked_add(base)
+            .ok_or(ClockError::InvalidEncoding)?;
+        match tag {
+            EVENT_LEAF => Ok(Event::Leaf(base)),
+            EVENT_NODE => {
+                let left = self.event(depth + 1, total)?;
+                let right = self.event(depth + 1, total)?;
+                let normalized = left.base().min(right.base()) == 0
+                    && !matches!((&left, &right), (Event::Leaf(a), Event::Leaf(b)) if a == b);
+                if !normalized {
+                    return Err(ClockError::InvalidEncoding);
+                }
+                Ok(Event::Node(base, Box::new(left), Box::new(right)))
+            }
+            _ => Err(ClockError::InvalidEncoding),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_fork_event_join() {
+        let (mut a, b) = Stamp::seed().fork();
+        let (mut b, mut c) = b.fork();
+        assert!(a.leq(&b) && b.leq(&a));
+
+        a.event().unwrap();
+        assert!(b.happened_before(&a));
+        b.event().unwrap();
+        c.event().unwrap();
+        assert!(a.concurrent_with(&b));
+        assert!(b.concurrent_with(&c));
+
+        let message = a.peek();
+        assert!(message.is_anonymous());
+        b = b.join(message);
+        assert!(a.happened_before(&b));
+        assert!(!b.is_anonymous());
+
+        // Retiring every participant gives back the whole interval.
+        let merged = a.join(b).join(c);
+        assert_eq!(merged.id, Id::One);
+        assert!(matches!(merged.event, Event::Leaf(_)));
+    }
+
+    #[test]
+    fn test_event_at() {
+        let mut stamp = Stamp::seed();
+        assert_eq!(stamp.hlc(), None);
+        assert_eq!(stamp.event_at(100), Ok(HybridLogicalClock::new(100)));
+        let second = stamp.event_at(100);
+        assert_eq!(
+            second,
+            Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 1))
+        );
+
+        let (mut a, b) = stamp.fork();
+        a.event_at(90).unwrap();
+        let b = b.join(a.peek());
+        assert_eq!(b.hlc(), a.hlc());
+    }
+
+    #[test]
+    fn test_receive_at() {
+        let (mut a, mut b) = Stamp::seed().fork();
+        let sent = a.event_at(100).unwrap();
+        b.event_at(50).unwrap();
+        let before = b.clone();
+
+        let received = b.receive_at(&a.peek(), 60).unwrap();
+        assert_eq!(
+            received,
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 1)
+        );
+        assert!(received > sent);
+        assert!(a.happened_before(&b));
+        assert!(before.happened_before(&b));
+        assert!(!b.is_anonymous());
+
+        // A clock ahead of the message resets the logical component.
+        let (mut c, _) = Stamp::seed().fork();
+        let received = c.receive_at(&a.peek(), 200).unwrap();
+        assert_eq!(received, HybridLogicalClock::new(200));
+
+        // Messages without a clock tick the receiver's clock.
+        let received = b.receive_at(&Stamp::seed().peek(), 100).unwrap();
+        assert_eq!(
+            received,
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 2)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "anonymous")]
+    fn test_anonymous_event_panics() {
+        Stamp::seed().peek().event().unwrap();
+    }
+
+    #[test]
+    fn test_count_overflow() {
+        let mut bytes = std::vec![ID_ONE, EVENT_LEAF];
+        write_varint(u64::MAX, &mut bytes);
+        bytes.push(0);
+        let mut full = Stamp::from_bytes(&bytes).unwrap();
+        assert_eq!(full.event.max(), u64::MAX);
+        assert_eq!(full.event(), Err(ClockError::EventCountOverflow));
+        assert_eq!(full.event_at(100), Err(ClockError::EventCountOverflow));
+        assert_eq!(full.to_bytes(), bytes);
+
+        // Joining and comparing only reuse counts the stamps already have.
+        let (mut a, b) = full.fork();
+        let mut other = Stamp::seed();
+        other.event().unwrap();
+        assert!(other.leq(&a));
+        a = a.join(other.peek()).join(b);
+        assert_eq!(a.event.max(), u64::MAX);
+
+        // A count of u64::MAX below the root, on the part of the interval the stamp owns.
+        let mut bytes = std::vec![ID_NODE, ID_ZERO, ID_ONE, EVENT_NODE];
+        write_varint(u64::MAX - 1, &mut bytes);
+        bytes.extend([EVENT_LEAF, 0, EVENT_LEAF, 1, 0]);
+        let mut stamp = Stamp::from_bytes(&bytes).unwrap();
+        assert_eq!(stamp.event(), Err(ClockError::EventCountOverflow));
+        assert_eq!(stamp.to_bytes(), bytes);
+
+        // Counts that add up past u64::MAX are rejected.
+        let mut bytes = std::vec![ID_ONE, EVENT_NODE];
+        write_varint(u64::MAX, &mut bytes);
+        bytes.extend([EVENT_LEAF, 0, EVENT_LEAF, 1, 0]);
+        assert_eq!(Stamp::from_bytes(&bytes), Err(ClockError::InvalidEncoding));
+    }
+
+    #[test]
+    fn test_encoding() {
+        assert_eq!(Stamp::seed().to_bytes(), [ID_ONE, EVENT_LEAF, 0, 0]);
+        assert_eq!(
+            Stamp::from_bytes(&[ID_ONE, EVENT_LEAF, 0]),
+            Err(ClockError::BufferTooSmall {
+                needed: 4,
+                actual: 3
+            })
+        );
+        // Trailing bytes, an unnormalized id, an unnormalized event tree and an overlong
+        // varint.
+        for bytes in [
+            &[ID_ONE, EVENT_LEAF, 0, 0, 0][..],
+            &[ID_NODE, ID_ONE, ID_ONE, EVENT_LEAF, 0, 0],
+            &[ID_ONE, EVENT_NODE, 0, EVENT_LEAF, 1, EVENT_LEAF, 1, 0],
+            &[ID_ONE, EVENT_LEAF, 0x80, 0x00, 0],
+        ] {
+            assert_eq!(Stamp::from_bytes(bytes), Err(ClockError::InvalidEncoding));
+        }
+
+        let mut deep = std::vec![ID_NODE; Stamp::MAX_DEPTH + 2];
+        deep.extend([ID_ONE, EVENT_LEAF, 0, 0]);
+        assert_eq!(Stamp::from_bytes(&deep), Err(ClockError::InvalidEncoding));
+    }
+
+    #[derive(Debug, Clone)]
+    enum Step {
+        Event(usize),
+        Fork(usize),
+        Join(usize, usize),
+    }
+
+    fn steps() -> impl Strategy<Value = Vec<Step>> {
+        proptest::collection::vec(
+            prop_oneof![
+                any::<usize>().prop_map(Step::Event),
+                any::<usize>().prop_map(Step::Fork),
+                (any::<usize>(), any::<usize>()).prop_map(|(a, b)| Step::Join(a, b)),
+            ],
+            0..60,
+        )
+    }
+
+    proptest! {
+        /// Runs random forks, events and joins against both stamps and a model that keeps
+        /// the explicit set of events each participant knows about, and checks that `leq`
+        /// is exactly set inclusion.
+        #[test]
+        fn prop_leq_matches_causal_histories(steps in steps()) {
+            let mut stamps = std::vec![(Stamp::seed(), BTreeSet::new())];
+            let mut next_event = 0u64;
+
+            for step in steps {
+                match step {
+                    Step::Event(i) => {
+                        let len = stamps.len();
+                        let (stamp, history) = &mut stamps[i % len];
+                        stamp.event().unwrap();
+                        history.insert(next_event);
+                        next_event += 1;
+                    }
+                    Step::Fork(i) => {
+                        let (stamp, history) = stamps.swap_remove(i % stamps.len());
+                        let (first, second) = stamp.fork();
+                        stamps.push((first, history.clone()));
+                        stamps.push((second, history));
+                    }
+                    Step::Join(i, j) => {
+                        if stamps.len() < 2 {
+                            continue;
+                        }
+                        let (second, second_history) = stamps.swap_remove(j % stamps.len());
+                        let (first, mut history) = stamps.swap_remove(i % stamps.len());
+                        history.extend(second_history);
+                        stamps.push((first.join(second), history));
+                    }
+                }
+
+                for (a, a_history) in &stamps {
+                    prop_assert_eq!(&Stamp::from_bytes(&a.to_bytes()), &Ok(a.clone()));
+                    for (b, b_history) in &stamps {
+                        prop_assert_eq!(a.leq(b), a_history.is_subset(b_history));
+                        prop_assert_eq!(a.peek().leq(b), a.leq(b));
+                    }
+                }
+            }
+
+            let merged = stamps
+                .into_iter()
+                .map(|(stamp, _)| stamp)
+                .reduce(Stamp::join)
+                .unwrap();
+            prop_assert_eq!(merged.id, Id::One);
+        }
+
+        #[test]
+        fn prop_from_bytes_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+            if let Ok(stamp) = Stamp::from_bytes(&bytes) {
+                prop_assert_eq!(stamp.to_bytes(), bytes);
+            }
+        }
+    }
+}
@@ -1,7 +1,67 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+#[cfg(target_has_atomic = "64")]
+mod atomic;
+mod bytes;
+#[cfg(feature = "alloc")]
+mod causal;
+mod clock;
+mod config;
+pub mod crdt;
+#[cfg(feature = "alloc")]
+pub mod dvv;
+mod error;
+mod format;
+#[cfg(feature = "alloc")]
+pub mod itc;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+mod packed;
+mod persistent;
+mod physical;
+#[cfg(feature = "alloc")]
+pub mod propagation;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(feature = "alloc")]
+mod stability;
+mod stamped;
+mod stats;
+mod timestamp;
+#[cfg(all(feature = "tracing", target_has_atomic = "64"))]
+pub mod tracing;
+#[cfg(feature = "alloc")]
+pub mod vector;
+
 use core::cmp::{max, Ordering};
 
+#[cfg(target_has_atomic = "64")]
+pub use atomic::AtomicHlc;
+#[cfg(feature = "alloc")]
+pub use causal::CausalContext;
+pub use clock::Clock;
+pub use config::{ClockConfig, OffsetPolicy, OverflowPolicy};
+pub use error::{ClockError, ParseError, PersistError};
+pub use packed::{PackedLayout, TimeUnit};
+#[cfg(feature = "std")]
+pub use persistent::FileStore;
+pub use persistent::{ClockStore, PersistentClock};
+pub use physical::{ManualClock, PhysicalClock};
+#[cfg(feature = "std")]
+pub use physical::{MonotonicAnchoredClock, SystemClock};
+#[cfg(feature = "alloc")]
+pub use stability::StabilityTracker;
+pub use stamped::Stamped;
+pub use stats::{ClockStats, SkewStats};
+pub use timestamp::{NodeId, Timestamp};
+
 /// Represents a Hybrid Logical Clock (HLC).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct HybridLogicalClock {
@@ -63,80 +123,360 @@ impl HybridLogicalClock {
         Self { physical, logical }
     }
 
-    /// Updates the clock based on a received timestamp.
-    /// 
+    /// Packs the clock into a single `u64` according to `layout`.
+    ///
+    /// Packed values compare as integers exactly as the clocks compare with [`Ord`], so
+    /// they can be used directly as index keys.
+    ///
     /// # Arguments
-    /// 
+    ///
+    /// * `layout` - The bit widths and time unit of the packed value.
+    ///
+    /// # Returns
+    ///
+    /// The packed value, or [`ClockError::PhysicalOutOfRange`] or
+    /// [`ClockError::LogicalOutOfRange`] if a component does not fit in its bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{HybridLogicalClock, PackedLayout};
+    ///
+    /// let layout = PackedLayout::default();
+    /// let earlier = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 9);
+    /// let later = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1001, 0);
+    ///
+    /// assert!(earlier.to_packed_u64(&layout).unwrap() < later.to_packed_u64(&layout).unwrap());
+    /// ```
+    pub fn to_packed_u64(&self, layout: &PackedLayout) -> Result<u64, ClockError> {
+        layout.pack(self)
+    }
+
+    /// Unpacks a clock from a value produced by [`HybridLogicalClock::to_packed_u64`].
+    ///
+    /// # Arguments
+    ///
+    /// * `packed` - The packed value.
+    /// * `layout` - The layout the value was packed with.
+    ///
+    /// # Returns
+    ///
+    /// The unpacked clock, or [`ClockError::PhysicalOutOfRange`] if bits above the layout are set.
+    pub fn from_packed_u64(packed: u64, layout: &PackedLayout) -> Result<Self, ClockError> {
+        layout.unpack(packed)
+    }
+
+    /// Advances the clock for a local or send event and returns the new timestamp.
+    ///
+    /// This is the send rule from Kulkarni et al.: the physical component becomes the
+    /// maximum of its previous value and `now`. If the physical component moved forward
+    /// the logical component is reset to 0, otherwise it is incremented so the returned
+    /// timestamp is strictly greater than every timestamp issued before it.
+    ///
+    /// If the logical component would overflow, the physical component is advanced by one
+    /// unit and the logical component is reset to 0 instead. See
+    /// [`HybridLogicalClock::checked_tick`] for a variant that fails instead.
+    ///
+    /// Once the physical component is `u64::MAX` and the logical component is at its
+    /// maximum, the clock cannot advance and this returns the current value again, so the
+    /// result is no longer strictly greater. Use [`HybridLogicalClock::checked_tick`] where
+    /// this must be detected.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current physical time.
+    ///
+    /// # Returns
+    ///
+    /// The new value of the clock, to be attached to the event or outgoing message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let mut hlc = HybridLogicalClock::new(100);
+    ///
+    /// // Wall time advanced, so the logical component is reset.
+    /// let t1 = hlc.tick(150);
+    /// assert_eq!((t1.physical, t1.logical), (150, 0));
+    ///
+    /// // Wall time stalled (or went backwards), so the logical component is incremented.
+    /// let t2 = hlc.tick(120);
+    /// assert_eq!((t2.physical, t2.logical), (150, 1));
+    /// assert!(t2 > t1);
+    /// ```
+    #[doc(alias = "send")]
+    pub fn tick(&mut self, now: u64) -> Self {
+        let next = self.send_rule(now);
+        self.advance(next, u32::MAX, OverflowPolicy::BorrowPhysical)
+            .unwrap_or(*self)
+    }
+
+    /// Advances the clock for a local or send event, failing instead of overflowing.
+    ///
+    /// This is [`HybridLogicalClock::tick`] without the borrowing from the physical
+    /// component: if the logical component would overflow, the clock is left unchanged.
+    ///
+    /// # Returns
+    ///
+    /// The new value of the clock, or [`ClockError::LogicalOverflow`] if the logical
+    /// component would exceed `u32::MAX`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{ClockError, HybridLogicalClock};
+    ///
+    /// let mut hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, u32::MAX);
+    /// assert!(matches!(hlc.checked_tick(100), Err(ClockError::LogicalOverflow { .. })));
+    /// assert_eq!(hlc.checked_tick(101), Ok(HybridLogicalClock::new(101)));
+    /// ```
+    pub fn checked_tick(&mut self, now: u64) -> Result<Self, ClockError> {
+        let next = self.send_rule(now);
+        self.advance(next, u32::MAX, OverflowPolicy::Reject)
+    }
+
+    /// Advances the clock for a send event and returns the timestamp to put on the message.
+    ///
+    /// This is the same operation as [`HybridLogicalClock::tick`], named after the "send or
+    /// local event" case of the algorithm for call sites where that reads better.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::HybridLogicalClock;
+    ///
+    /// let mut hlc = HybridLogicalClock::new(100);
+    /// let outgoing = hlc.send(100);
+    /// assert_eq!((outgoing.physical, outgoing.logical), (100, 1));
+    /// ```
+    pub fn send(&mut self, now: u64) -> Self {
+        self.tick(now)
+    }
+
+    /// Updates the clock based on a received timestamp and returns the new timestamp.
+    ///
+    /// This is the receive rule from Kulkarni et al.: the physical component becomes the
+    /// maximum of the previous local value, the received value and `now`. The logical
+    /// component then continues from whichever of the local and received timestamps
+    /// carried that physical time (the larger of the two if both did), and is reset to 0
+    /// when only `now` did.
+    ///
+    /// If the logical component would overflow, the physical component is advanced by one
+    /// unit and the logical component is reset to 0 instead. See
+    /// [`HybridLogicalClock::checked_update`] for a variant that fails instead.
+    ///
+    /// Once the physical component is `u64::MAX` and the logical component is at its
+    /// maximum, the clock cannot advance and this returns the current value again, so the
+    /// result is no longer strictly greater. Use [`HybridLogicalClock::checked_update`] where
+    /// this must be detected.
+    ///
+    /// # Arguments
+    ///
     /// * `received` - The received HybridLogicalClock.
     /// * `now` - The current physical time.
-    /// 
+    ///
+    /// # Returns
+    ///
+    /// The new value of the clock, which is greater than both the previous value and `received`.
+    ///
     /// # Example
-    /// 
+    ///
     /// ```
     /// use hybrid_logical_clock::HybridLogicalClock;
-    /// 
+    ///
     /// let mut hlc = HybridLogicalClock::new(100);
     /// let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(150, 10);
+    ///
+    /// // Wall time is ahead of both timestamps, so the logical component is reset.
     /// hlc.update(&received, 200);
-    /// 
     /// assert_eq!(hlc.physical, 200);
-    /// assert_eq!(hlc.logical, 1);
-    /// ```
-    pub fn update(&mut self, received: &Self, now: u64) {
-        // Update the physical time to the maximum of the current physical time, the received physical time, and the current time.
-        self.physical = max(max(self.physical, received.physical), now);
-        // If the physical time is the same as the received physical time and the current time, update the logical time.
-        if self.physical == received.physical && self.physical == now {
-            self.logical = max(self.logical, received.logical) + 1;
-        } else if self.physical == received.physical {
-            self.logical = max(self.logical, received.logical) + 1;
-        } else if self.physical == now {
-            self.logical += 1;
-        } else {
-            self.logical = 0;
-        }
+    /// assert_eq!(hlc.logical, 0);
+    ///
+    /// // The received timestamp is ahead of wall time, so its logical component is continued.
+    /// let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(300, 10);
+    /// let updated = hlc.update(&received, 250);
+    /// assert_eq!(updated.physical, 300);
+    /// assert_eq!(updated.logical, 11);
+    /// ```
+    pub fn update(&mut self, received: &Self, now: u64) -> Self {
+        let next = self.receive_rule(received, now);
+        self.advance(next, u32::MAX, OverflowPolicy::BorrowPhysical)
+            .unwrap_or(*self)
     }
 
-    /// Checks if this clock is concurrent with another hybrid logical clock.
-    /// 
-    /// This method is crucial for determining the causal relationship between events in a distributed system.
-    /// It's particularly useful when you need to detect and handle concurrent operations or updates.
-    /// 
-    /// Use this method when:
-    /// 1. Implementing conflict resolution strategies in distributed databases or systems.
-    /// 2. Detecting potential conflicts in collaborative editing systems.
-    /// 3. Ensuring consistency in replicated state machines.
-    /// 4. Implementing version control systems that need to handle parallel changes.
-    /// 
-    /// The importance of this method lies in its ability to identify events that happened without knowledge
-    /// of each other. In distributed systems, this information is vital for maintaining consistency and
-    /// correctly merging or reconciling divergent states.
-    /// 
+    /// Updates the clock based on a received timestamp, failing instead of overflowing.
+    ///
+    /// This is [`HybridLogicalClock::update`] without the borrowing from the physical
+    /// component: if the logical component would overflow, the clock is left unchanged.
+    ///
+    /// # Returns
+    ///
+    /// The new value of the clock, or [`ClockError::LogicalOverflow`] if the logical
+    /// component would exceed `u32::MAX`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{ClockError, HybridLogicalClock};
+    ///
+    /// let mut hlc = HybridLogicalClock::new(100);
+    /// let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, u32::MAX);
+    /// assert!(matches!(hlc.checked_update(&received, 100), Err(ClockError::LogicalOverflow { .. })));
+    /// assert_eq!(hlc, HybridLogicalClock::new(100));
+    /// ```
+    pub fn checked_update(&mut self, received: &Self, now: u64) -> Result<Self, ClockError> {
+        let next = self.receive_rule(received, now);
+        self.advance(next, u32::MAX, OverflowPolicy::Reject)
+    }
+
+    /// Updates the clock based on a received timestamp unless it is too far in the future.
+    ///
+    /// A node with a badly wrong wall clock would otherwise drag the physical component of
+    /// every peer forward with it, and a hybrid logical clock can never move back.
+    ///
     /// # Arguments
-    /// 
-    /// * `other` - The other HybridLogicalClock to compare with.
-    /// 
+    ///
+    /// * `received` - The received HybridLogicalClock.
+    /// * `now` - The current physical time.
+    /// * `max_offset` - How far `received.physical` may be ahead of `now`.
+    ///
     /// # Returns
-    /// 
-    /// `true` if the clocks are concurrent, `false` otherwise.
-    /// 
+    ///
+    /// The new value of the clock, or [`ClockError::OffsetExceeded`] if the received
+    /// physical time is more than `max_offset` ahead of `now`. The clock is unchanged on error.
+    ///
     /// # Example
-    /// 
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{ClockError, HybridLogicalClock};
+    ///
+    /// let mut hlc = HybridLogicalClock::new(1000);
+    /// let received = HybridLogicalClock::new(1500);
+    ///
+    /// assert_eq!(
+    ///     hlc.try_update(&received, 1000, 100),
+    ///     Err(ClockError::OffsetExceeded { received: 1500, now: 1000, max: 100 })
+    /// );
+    /// assert_eq!(hlc, HybridLogicalClock::new(1000));
+    ///
+    /// assert!(hlc.try_update(&received, 1450, 100).is_ok());
+    /// assert_eq!(hlc.physical, 1500);
+    /// ```
+    pub fn try_update(
+        &mut self,
+        received: &Self,
+        now: u64,
+        max_offset: u64,
+    ) -> Result<Self, ClockError> {
+        check_offset(received, now, max_offset)?;
+        Ok(self.update(received, now))
+    }
+
+    /// Returns the physical and (unbounded) logical components after a send event.
+    pub(crate) fn send_rule(&self, now: u64) -> (u64, u64) {
+        if now > self.physical {
+            (now, 0)
+        } else {
+            (self.physical, u64::from(self.logical) + 1)
+        }
+    }
+
+    /// Returns the physical and (unbounded) logical components after a receive event.
+    pub(crate) fn receive_rule(&self, received: &Self, now: u64) -> (u64, u64) {
+        // The physical time is the maximum of the previous physical time, the received physical time, and the current time.
+        let physical = max(max(self.physical, received.physical), now);
+        // The logical time continues from every timestamp that already carried that physical time.
+        let logical = if physical == self.physical && physical == received.physical {
+            u64::from(max(self.logical, received.logical)) + 1
+        } else if physical == self.physical {
+            u64::from(self.logical) + 1
+        } else if physical == received.physical {
+            u64::from(received.logical) + 1
+        } else {
+            0
+        };
+        (physical, logical)
+    }
+
+    /// Moves the clock to `next`, applying `policy` if its logical component exceeds `max_logical`.
+    pub(crate) fn advance(
+        &mut self,
+        (physical, logical): (u64, u64),
+        max_logical: u32,
+        policy: OverflowPolicy,
+    ) -> Result<Self, ClockError> {
+        let next = match u32::try_from(logical) {
+            Ok(logical) if logical <= max_logical => {
+                Self::new_with_both_physical_and_logical_clock_time(physical, logical)
+            }
+            _ => {
+                let overflow = ClockError::LogicalOverflow {
+                    physical,
+                    max: max_logical,
+                };
+                match policy {
+                    OverflowPolicy::Reject => return Err(overflow),
+                    OverflowPolicy::BorrowPhysical => {
+                        Self::new(physical.checked_add(1).ok_or(overflow)?)
+                    }
+                }
+            }
+        };
+        *self = next;
+        Ok(next)
+    }
+
+    /// Checks whether two clocks have the same physical component but different logical components.
+    ///
+    /// Despite its name, this does not detect concurrency. Hybrid logical clock timestamps
+    /// are consistent with causality (if `a` happened before `b` then `a < b`), but the
+    /// converse does not hold, so no comparison of two timestamps can tell whether the
+    /// events were concurrent. Two causally related events can share a physical time, and
+    /// two concurrent events can have different ones.
+    ///
+    /// # Migration
+    ///
+    /// * If you need an order between events, for example for last-writer-wins, compare
+    ///   the clocks (or [`Timestamp`]s) with [`Ord`].
+    /// * If you need to detect concurrent updates, track a `CausalContext` alongside the
+    ///   clock and use `CausalContext::concurrent_with`. It requires the `alloc` feature.
+    /// * If you relied on the exact behavior, compare the fields directly:
+    ///   `a.physical == b.physical && a.logical != b.logical`.
+    ///
+    /// # Example
+    ///
     /// ```
+    /// # #![allow(deprecated)]
     /// use hybrid_logical_clock::HybridLogicalClock;
-    /// 
+    ///
     /// let hlc1 = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 5);
     /// let hlc2 = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 10);
     /// assert!(hlc1.is_concurrent(&hlc2));
-    /// 
-    /// // Non-concurrent example
-    /// let hlc3 = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(101, 1);
-    /// assert!(!hlc1.is_concurrent(&hlc3));
     /// ```
+    #[deprecated(
+        note = "HLC timestamps cannot detect concurrency; compare with `Ord` or use `CausalContext::concurrent_with`"
+    )]
     pub fn is_concurrent(&self, other: &Self) -> bool {
         self.physical == other.physical && self.logical != other.logical
     }
+}
 
+/// Returns an error if `received` is more than `max_offset` ahead of `now`.
+pub(crate) fn check_offset(
+    received: &HybridLogicalClock,
+    now: u64,
+    max_offset: u64,
+) -> Result<(), ClockError> {
+    if received.physical > now.saturating_add(max_offset) {
+        return Err(ClockError::OffsetExceeded {
+            received: received.physical,
+            now,
+            max: max_offset,
+        });
+    }
+    Ok(())
 }
 
 impl PartialOrd for HybridLogicalClock {
@@ -157,6 +497,7 @@ impl Ord for HybridLogicalClock {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_new() {
@@ -178,10 +519,156 @@ mod tests {
         let hlc2 = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(150, 10);
         hlc1.update(&hlc2, 200);
         assert_eq!(hlc1.physical, 200);
-        assert_eq!(hlc1.logical, 1);
+        assert_eq!(hlc1.logical, 0);
+    }
+
+    /// The receive rule exactly as written in Kulkarni et al., "Logical Physical Clocks and
+    /// Consistent Snapshots in Globally Distributed Databases", Figure 5.
+    fn reference_update(
+        local: HybridLogicalClock,
+        received: HybridLogicalClock,
+        pt: u64,
+    ) -> HybridLogicalClock {
+        let (l, c) = (local.physical, local.logical);
+        let (l_m, c_m) = (received.physical, received.logical);
+        let l_new = max(max(l, l_m), pt);
+        let c_new = if l_new == l && l_new == l_m {
+            max(c, c_m) + 1
+        } else if l_new == l {
+            c + 1
+        } else if l_new == l_m {
+            c_m + 1
+        } else {
+            0
+        };
+        HybridLogicalClock::new_with_both_physical_and_logical_clock_time(l_new, c_new)
+    }
+
+    #[test]
+    fn test_update_matches_reference_exhaustively() {
+        const PHYSICAL: u64 = 5;
+        const LOGICAL: u32 = 4;
+        for local_physical in 0..PHYSICAL {
+            for local_logical in 0..LOGICAL {
+                for received_physical in 0..PHYSICAL {
+                    for received_logical in 0..LOGICAL {
+                        for now in 0..PHYSICAL {
+                            let local =
+                                HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                                    local_physical,
+                                    local_logical,
+                                );
+                            let received =
+                                HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                                    received_physical,
+                                    received_logical,
+                                );
+                            let expected = reference_update(local, received, now);
+
+                            let mut hlc = local;
+                            let returned = hlc.update(&received, now);
+                            assert_eq!(
+                                returned, expected,
+                                "update({local:?}, {received:?}, {now})"
+                            );
+                            assert_eq!(hlc, expected);
+                            assert!(hlc > local);
+                            assert!(hlc > received);
+                            assert!(hlc.physical >= now);
+                        }
+                    }
+                }
+            }
+        }
     }
 
     #[test]
+    fn test_try_update() {
+        let mut hlc = HybridLogicalClock::new(1000);
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1100, 3);
+        assert_eq!(
+            hlc.try_update(&received, 1000, 99),
+            Err(ClockError::OffsetExceeded {
+                received: 1100,
+                now: 1000,
+                max: 99
+            })
+        );
+        assert_eq!(hlc, HybridLogicalClock::new(1000));
+        assert_eq!(
+            hlc.try_update(&received, 1000, 100),
+            Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1100, 4))
+        );
+        // A received time behind the local clock is never an offset violation.
+        assert!(hlc.try_update(&received, u64::MAX, 0).is_ok());
+    }
+
+    #[test]
+    fn test_logical_overflow_borrows_physical() {
+        let mut hlc =
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, u32::MAX);
+        assert_eq!(hlc.tick(100), HybridLogicalClock::new(101));
+
+        let mut hlc = HybridLogicalClock::new(100);
+        let received =
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(200, u32::MAX);
+        assert_eq!(hlc.update(&received, 150), HybridLogicalClock::new(201));
+    }
+
+    #[test]
+    fn test_saturates_at_the_last_timestamp() {
+        // The last representable timestamp cannot advance any further, so tick and update
+        // return it again while the checked variants report the overflow.
+        let last =
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(u64::MAX, u32::MAX);
+        let mut hlc = last;
+        assert_eq!(hlc.tick(0), last);
+        assert_eq!(hlc.update(&last, 0), last);
+        assert_eq!(hlc, last);
+
+        let overflow = ClockError::LogicalOverflow {
+            physical: u64::MAX,
+            max: u32::MAX,
+        };
+        assert_eq!(hlc.checked_tick(u64::MAX), Err(overflow));
+        assert_eq!(hlc.checked_update(&last, 0), Err(overflow));
+    }
+
+    #[test]
+    fn test_checked_overflow() {
+        let start =
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, u32::MAX);
+        let mut hlc = start;
+        assert_eq!(
+            hlc.checked_tick(100),
+            Err(ClockError::LogicalOverflow {
+                physical: 100,
+                max: u32::MAX
+            })
+        );
+        assert_eq!(
+            hlc.checked_update(&HybridLogicalClock::new(50), 90),
+            Err(ClockError::LogicalOverflow {
+                physical: 100,
+                max: u32::MAX
+            })
+        );
+        assert_eq!(hlc, start);
+        assert_eq!(
+            hlc.checked_update(&start, 101),
+            Ok(HybridLogicalClock::new(101))
+        );
+    }
+
+    #[test]
+    fn test_update_resets_logical_when_now_is_ahead() {
+        let mut hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 7);
+        let received = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 9);
+        assert_eq!(hlc.update(&received, 101), HybridLogicalClock::new(101));
+    }
+
+    #[test]
+    #[allow(deprecated)]
     fn test_is_concurrent() {
         let hlc1 = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 5);
         let hlc2 = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 10);
@@ -196,4 +683,42 @@ mod tests {
         assert!(hlc1 < hlc2);
         assert!(hlc2 < hlc3);
     }
+
+    #[test]
+    fn test_tick() {
+        let mut hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(100, 7);
+        assert_eq!(hlc.tick(200), HybridLogicalClock::new(200));
+        assert_eq!(
+            hlc.tick(200),
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(200, 1)
+        );
+        assert_eq!(
+            hlc.send(50),
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(200, 2)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_tick_is_strictly_monotonic(
+            start in any::<u32>(),
+            nows in proptest::collection::vec(any::<u32>(), 1..64),
+        ) {
+            let mut hlc = HybridLogicalClock::new(u64::from(start));
+            let mut previous = hlc;
+            for now in nows {
+                let now = u64::from(now);
+                let next = hlc.tick(now);
+                prop_assert!(next > previous);
+                prop_assert!(next.physical >= now);
+                prop_assert_eq!(next.physical, max(previous.physical, now));
+                if next.physical == previous.physical {
+                    prop_assert_eq!(next.logical, previous.logical + 1);
+                } else {
+                    prop_assert_eq!(next.logical, 0);
+                }
+                previous = next;
+            }
+        }
+    }
 }
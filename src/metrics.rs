@@ -0,0 +1,205 @@
+//! Clock health metrics, emitted through the [`metrics`](https://docs.rs/metrics) crate.
+//!
+//! Every [`Clock`](crate::Clock) reports to the installed recorder at the same points where
+//! it updates its [`ClockStats`](crate::ClockStats). The metric names are stable and can be
+//! used in dashboards and alerts. Times are in the clock's unit, which is milliseconds for
+//! the physical clocks in this crate.
+//!
+//! # Example
+//!
+//! ```
+//! use hybrid_logical_clock::{metrics, Clock, HybridLogicalClock, ManualClock};
+//!
+//! // Install a recorder, such as a Prometheus exporter, then describe the metrics once.
+//! metrics::describe();
+//!
+//! let mut clock = Clock::new(ManualClock::new(1000));
+//! clock.update(&HybridLogicalClock::new(1250));
+//! // Recorded `hlc_peer_skew` 250, `hlc_logical` 1 and `hlc_wall_gap` 250.
+//! ```
+
+use ::metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use std::string::ToString;
+
+use crate::{ClockError, HybridLogicalClock, NodeId};
+
+/// Histogram of how far received physical times were ahead of the local physical time.
+/// Negative values mean the received time was behind.
+///
+/// Accepted updates from the peers tracked in [`ClockStats`](crate::ClockStats) carry a
+/// `peer` label with their node id. Node ids come from the network, so
+/// [`ClockConfig::with_max_peers`](crate::ClockConfig::with_max_peers) bounds the number of
+/// labelled series. Other updates are recorded without the label.
+pub const PEER_SKEW: &str = "hlc_peer_skew";
+
+/// Histogram of the logical component of every issued timestamp.
+pub const LOGICAL: &str = "hlc_logical";
+
+/// Gauge of how far the physical component of the last issued timestamp is ahead of the
+/// wall clock. It grows when peers with fast clocks or bursts of events push the clock ahead.
+pub const WALL_GAP: &str = "hlc_wall_gap";
+
+/// Counter of received timestamps rejected by the checked updates. The `reason` label is
+/// `offset_exceeded`, `logical_limit_exceeded` or `logical_overflow`.
+pub const REJECTED_UPDATES: &str = "hlc_rejected_updates_total";
+
+/// Counter of received timestamps that exceeded the maximum offset but were absorbed under
+/// [`OffsetPolicy::AcceptAndReport`](crate::OffsetPolicy::AcceptAndReport).
+pub const OFFSET_VIOLATIONS: &str = "hlc_offset_violations_total";
+
+/// Registers descriptions for every metric with the installed recorder.
+pub fn describe() {
+    describe_histogram!(
+        PEER_SKEW,
+        "Offset of received physical times from the local physical time"
+    );
+    describe_histogram!(LOGICAL, "Logical component of issued timestamps");
+    describe_gauge!(
+        WALL_GAP,
+        "Physical component of the last issued timestamp minus the wall clock"
+    );
+    describe_counter!(
+        REJECTED_UPDATES,
+        "Received timestamps rejected by checked updates"
+    );
+    describe_counter!(
+        OFFSET_VIOLATIONS,
+        "Received timestamps beyond the maximum offset that were accepted"
+    );
+}
+
+pub(crate) fn record_offset(peer: Option<NodeId>, offset: i64) {
+    match peer {
+        Some(peer) => histogram!(PEER_SKEW, "peer" => peer.to_string()).record(offset as f64),
+        None => histogram!(PEER_SKEW).record(offset as f64),
+    }
+}
+
+pub(crate) fn record_event(hlc: HybridLogicalClock, wall_gap: i64) {
+    histogram!(LOGICAL).record(f64::from(hlc.logical));
+    gauge!(WALL_GAP).set(wall_gap as f64);
+}
+
+pub(crate) fn record_rejection(error: &ClockError) {
+    let reason = match error {
+        ClockError::OffsetExceeded { .. } => "offset_exceeded",
+        ClockError::LogicalLimitExceeded { .. } => "logical_limit_exceeded",
+        ClockError::LogicalOverflow { .. } => "logical_overflow",
+        _ => "other",
+    };
+    counter!(REJECTED_UPDATES, "reason" => reason).increment(1);
+}
+
+pub(crate) fn record_offset_violation() {
+    counter!(OFFSET_VIOLATIONS).increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Clock, ClockConfig, ManualClock, OffsetPolicy, Timestamp};
+    use ::metrics::with_local_recorder;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use metrics_util::MetricKind;
+    use std::string::String;
+    use std::vec::Vec;
+
+    fn labels(key: &::metrics::Key) -> Vec<(String, String)> {
+        key.labels()
+            .map(|label| (label.key().to_string(), label.value().to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_clock_emits_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let wall = ManualClock::new(1000);
+        let config = ClockConfig::new().with_max_offset(500, OffsetPolicy::Reject);
+        let mut clock = Clock::new(&wall).with_config(config);
+
+        with_local_recorder(&recorder, || {
+            describe();
+            clock.tick();
+            let received = Timestamp::new(HybridLogicalClock::new(1250), NodeId(3));
+            clock.try_update_from(&received).unwrap();
+            // A rejected sender gets no label of its own.
+            let rejected = Timestamp::new(HybridLogicalClock::new(9000), NodeId(4));
+            assert!(clock.try_update_from(&rejected).is_err());
+            wall.advance(1000);
+            clock.tick();
+        });
+
+        let snapshot = snapshotter.snapshot().into_hashmap();
+        let mut seen = Vec::new();
+        for (key, (_, description, value)) in snapshot {
+            let (kind, key) = key.into_parts();
+            assert!(description.is_some(), "{} is not described", key.name());
+            seen.push(key.name().to_string());
+            match (kind, key.name(), value) {
+                (MetricKind::Histogram, PEER_SKEW, DebugValue::Histogram(values)) => {
+                    let values: Vec<f64> = values.into_iter().map(|v| v.into_inner()).collect();
+                    match labels(&key).as_slice() {
+                        [] => assert_eq!(values, [8000.0]),
+                        [(name, peer)] => {
+                            assert_eq!((name.as_str(), peer.as_str()), ("peer", "3"));
+                            assert_eq!(values, [250.0]);
+                        }
+                        other => panic!("unexpected labels {other:?}"),
+                    }
+                }
+                (MetricKind::Histogram, LOGICAL, DebugValue::Histogram(values)) => {
+                    let values: Vec<f64> = values.into_iter().map(|v| v.into_inner()).collect();
+                    assert_eq!(values, [1.0, 1.0, 0.0]);
+                }
+                (MetricKind::Gauge, WALL_GAP, DebugValue::Gauge(gap)) => {
+                    // The clock caught up with the wall clock on the last tick.
+                    assert_eq!(gap.into_inner(), 0.0);
+                }
+                (MetricKind::Counter, REJECTED_UPDATES, DebugValue::Counter(count)) => {
+                    let reason = (String::from("reason"), String::from("offset_exceeded"));
+                    assert_eq!(labels(&key), [reason]);
+                    assert_eq!(count, 1);
+                }
+                (kind, name, value) => panic!("unexpected {kind:?} {name} {value:?}"),
+            }
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen, [LOGICAL, PEER_SKEW, REJECTED_UPDATES, WALL_GAP]);
+        assert_eq!(clock.stats().rejected_updates(), 1);
+    }
+
+    #[test]
+    fn test_clock_reports_offset_violations() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let config = ClockConfig::new().with_max_offset(500, OffsetPolicy::AcceptAndReport);
+        let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+
+        with_local_recorder(&recorder, || {
+            describe();
+            let ahead = Timestamp::new(HybridLogicalClock::new(9000), NodeId(4));
+            clock.try_update_from(&ahead).unwrap();
+        });
+
+        let snapshot = snapshotter.snapshot().into_hashmap();
+        let mut seen = Vec::new();
+        for (key, (_, description, value)) in snapshot {
+            let (kind, key) = key.into_parts();
+            seen.push(key.name().to_string());
+            if key.name() == OFFSET_VIOLATIONS {
+                assert_eq!(kind, MetricKind::Counter);
+                assert_eq!(value, DebugValue::Counter(1));
+                assert_eq!(
+                    description.as_deref(),
+                    Some("Received timestamps beyond the maximum offset that were accepted")
+                );
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, [LOGICAL, OFFSET_VIOLATIONS, PEER_SKEW, WALL_GAP]);
+        assert_eq!(clock.stats().offset_violations(), 1);
+        assert_eq!(clock.stats().rejected_updates(), 0);
+    }
+}
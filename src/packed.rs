@@ -0,0 +1,386 @@
+use core::time::Duration;
+
+use crate::{ClockError, HybridLogicalClock};
+
+/// The unit of the physical component of a packed clock.
+///
+/// Clocks in this crate count milliseconds. A [`PackedLayout`] converts the physical
+/// component to its unit when packing and back to milliseconds when unpacking.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TimeUnit {
+    /// Seconds since the Unix epoch, as in MongoDB's cluster time.
+    Seconds,
+    /// Milliseconds since the Unix epoch, the convention used throughout this crate.
+    #[default]
+    Milliseconds,
+    /// Microseconds since the Unix epoch.
+    Microseconds,
+    /// Nanoseconds since the Unix epoch.
+    Nanoseconds,
+}
+
+impl TimeUnit {
+    /// Returns the duration of `count` units.
+    pub const fn duration(self, count: u64) -> Duration {
+        match self {
+            TimeUnit::Seconds => Duration::from_secs(count),
+            TimeUnit::Milliseconds => Duration::from_millis(count),
+            TimeUnit::Microseconds => Duration::from_micros(count),
+            TimeUnit::Nanoseconds => Duration::from_nanos(count),
+        }
+    }
+
+    /// Converts `millis` to this unit, rounding down. Returns `None` on overflow.
+    pub(crate) const fn to_units(self, millis: u64) -> Option<u64> {
+        match self {
+            TimeUnit::Seconds => Some(millis / 1000),
+            TimeUnit::Milliseconds => Some(millis),
+            TimeUnit::Microseconds => millis.checked_mul(1000),
+            TimeUnit::Nanoseconds => millis.checked_mul(1_000_000),
+        }
+    }
+
+    /// Converts `count` units to milliseconds, rounding down. Returns `None` on overflow.
+    pub(crate) const fn to_millis(self, count: u64) -> Option<u64> {
+        match self {
+            TimeUnit::Seconds => count.checked_mul(1000),
+            TimeUnit::Milliseconds => Some(count),
+            TimeUnit::Microseconds => Some(count / 1000),
+            TimeUnit::Nanoseconds => Some(count / 1_000_000),
+        }
+    }
+}
+
+/// How a [`HybridLogicalClock`] is packed into a single `u64`.
+///
+/// The logical component occupies the low `logical_bits` bits and the physical component,
+/// counted in the layout's [`TimeUnit`], the `physical_bits` bits above it. Any remaining
+/// high bits are zero. Because the physical component is more significant, comparing
+/// packed values as integers gives the same result as comparing the clocks themselves,
+/// which makes packed values usable as index keys.
+///
+/// The default layout is 48 bits of milliseconds and 16 bits of logical counter, as used
+/// by CockroachDB-style hybrid logical clocks. MongoDB's cluster time is 32 bits of
+/// seconds and 32 bits of counter.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{HybridLogicalClock, PackedLayout, TimeUnit};
+///
+/// let layout = PackedLayout::new(48, 16, TimeUnit::Milliseconds);
+/// let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 5);
+///
+/// let packed = hlc.to_packed_u64(&layout).unwrap();
+/// assert_eq!(packed, 1000 << 16 | 5);
+/// assert_eq!(HybridLogicalClock::from_packed_u64(packed, &layout), Ok(hlc));
+///
+/// let mongodb = PackedLayout::new(32, 32, TimeUnit::Seconds);
+/// assert_eq!(hlc.to_packed_u64(&mongodb), Ok(1 << 32 | 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedLayout {
+    physical_bits: u32,
+    logical_bits: u32,
+    unit: TimeUnit,
+}
+
+impl PackedLayout {
+    /// Creates a new PackedLayout.
+    ///
+    /// # Arguments
+    ///
+    /// * `physical_bits` - The number of bits for the physical component.
+    /// * `logical_bits` - The number of bits for the logical component.
+    /// * `unit` - The unit the physical component counts since the Unix epoch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either width is 0, if `logical_bits` is greater than 32, or if the widths
+    /// add up to more than 64.
+    pub const fn new(physical_bits: u32, logical_bits: u32, unit: TimeUnit) -> Self {
+        assert!(physical_bits > 0, "physical_bits must not be 0");
+        assert!(
+            logical_bits > 0 && logical_bits <= 32,
+            "logical_bits must be between 1 and 32"
+        );
+        assert!(
+            physical_bits + logical_bits <= 64,
+            "a packed layout cannot exceed 64 bits"
+        );
+        Self {
+            physical_bits,
+            logical_bits,
+            unit,
+        }
+    }
+
+    /// Returns the number of bits for the physical component.
+    pub const fn physical_bits(&self) -> u32 {
+        self.physical_bits
+    }
+
+    /// Returns the number of bits for the logical component.
+    pub const fn logical_bits(&self) -> u32 {
+        self.logical_bits
+    }
+
+    /// Returns the unit of the physical component.
+    pub const fn unit(&self) -> TimeUnit {
+        self.unit
+    }
+
+    /// Returns the largest physical component that fits in the layout, in the layout's
+    /// unit.
+    pub const fn max_physical(&self) -> u64 {
+        u64::MAX >> (64 - self.physical_bits)
+    }
+
+    /// Returns the largest logical component that fits in the layout.
+    pub const fn max_logical(&self) -> u32 {
+        u32::MAX >> (32 - self.logical_bits)
+    }
+
+    /// Returns the latest time since the Unix epoch that the layout can represent.
+    pub const fn max_time(&self) -> Duration {
+        self.unit.duration(self.max_physical())
+    }
+
+    /// Packs `hlc` into a `u64`.
+    ///
+    /// # Returns
+    ///
+    /// The packed value, or [`ClockError::LogicalOutOfRange`] if the logical component
+    /// does not fit in its bits. [`ClockError::PhysicalOutOfRange`] is returned if the
+    /// physical component does not fit in its bits, or if it is not a whole number of the
+    /// layout's unit, so packing never loses precision.
+    pub fn pack(&self, hlc: &HybridLogicalClock) -> Result<u64, ClockError> {
+        let physical = match self.unit.to_units(hlc.physical) {
+            Some(physical) if self.unit.to_millis(physical) == Some(hlc.physical) => physical,
+            _ => return Err(self.physical_out_of_range(hlc.physical)),
+        };
+        self.pack_units(physical, hlc.logical)
+    }
+
+    /// Unpacks a value produced by [`PackedLayout::pack`].
+    ///
+    /// Physical components in units finer than milliseconds are rounded down, which only
+    /// happens for values that were not produced by [`PackedLayout::pack`].
+    ///
+    /// # Returns
+    ///
+    /// The unpacked clock, or [`ClockError::PhysicalOutOfRange`] if bits above the
+    /// layout are set or the physical component overflows milliseconds.
+    pub fn unpack(&self, packed: u64) -> Result<HybridLogicalClock, ClockError> {
+        let (physical, logical) = self.unpack_units(packed)?;
+        let physical = self.millis(physical)?;
+        Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical))
+    }
+
+    /// Packs a physical component that is already in the layout's unit.
+    pub(crate) fn pack_units(&self, physical: u64, logical: u32) -> Result<u64, ClockError> {
+        if physical > self.max_physical() {
+            return Err(
+                self.physical_out_of_range(self.unit.to_millis(physical).unwrap_or(u64::MAX))
+            );
+        }
+        if logical > self.max_logical() {
+            return Err(ClockError::LogicalOutOfRange {
+                logical,
+                max: self.max_logical(),
+            });
+        }
+        Ok(physical << self.logical_bits | u64::from(logical))
+    }
+
+    /// Unpacks a value without converting its physical component from the layout's unit.
+    pub(crate) fn unpack_units(&self, packed: u64) -> Result<(u64, u32), ClockError> {
+        let physical = packed >> self.logical_bits;
+        if physical > self.max_physical() {
+            return Err(
+                self.physical_out_of_range(self.unit.to_millis(physical).unwrap_or(u64::MAX))
+            );
+        }
+        Ok((physical, (packed & u64::from(self.max_logical())) as u32))
+    }
+
+    /// Converts a physical component in the layout's unit to milliseconds.
+    pub(crate) fn millis(&self, physical: u64) -> Result<u64, ClockError> {
+        self.unit
+            .to_millis(physical)
+            .ok_or(self.physical_out_of_range(u64::MAX))
+    }
+
+    /// Returns the error for a physical time, in milliseconds, that the layout cannot hold.
+    pub(crate) fn physical_out_of_range(&self, physical: u64) -> ClockError {
+        ClockError::PhysicalOutOfRange {
+            physical,
+            // Only seconds can overflow when converted to milliseconds.
+            max: self
+                .unit
+                .to_millis(self.max_physical())
+                .unwrap_or(u64::MAX / 1000 * 1000),
+        }
+    }
+}
+
+impl Default for PackedLayout {
+    fn default() -> Self {
+        Self::new(48, 16, TimeUnit::Milliseconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const UNITS: [TimeUnit; 4] = [
+        TimeUnit::Seconds,
+        TimeUnit::Milliseconds,
+        TimeUnit::Microseconds,
+        TimeUnit::Nanoseconds,
+    ];
+
+    #[test]
+    fn test_layout_bounds() {
+        let layout = PackedLayout::default();
+        assert_eq!(layout.max_physical(), (1 << 48) - 1);
+        assert_eq!(layout.max_logical(), u32::from(u16::MAX));
+        // 2^48 milliseconds is a little under 8920 years.
+        assert_eq!(layout.max_time().as_secs() / 31_557_600, 8919);
+
+        let full = PackedLayout::new(32, 32, TimeUnit::Milliseconds);
+        let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+            u32::MAX.into(),
+            u32::MAX,
+        );
+        assert_eq!(full.pack(&hlc), Ok(u64::MAX));
+        assert_eq!(full.unpack(u64::MAX), Ok(hlc));
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let layout = PackedLayout::new(8, 4, TimeUnit::Milliseconds);
+        assert_eq!(
+            layout.pack(&HybridLogicalClock::new(256)),
+            Err(ClockError::PhysicalOutOfRange {
+                physical: 256,
+                max: 255
+            })
+        );
+        assert_eq!(
+            layout.pack(&HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1, 16)),
+            Err(ClockError::LogicalOutOfRange {
+                logical: 16,
+                max: 15
+            })
+        );
+        assert_eq!(
+            layout.unpack(1 << 12),
+            Err(ClockError::PhysicalOutOfRange {
+                physical: 256,
+                max: 255
+            })
+        );
+    }
+
+    #[test]
+    fn test_units() {
+        let seconds = PackedLayout::new(32, 32, TimeUnit::Seconds);
+        assert_eq!(seconds.max_time().as_secs(), u64::from(u32::MAX));
+        let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(7000, 2);
+        assert_eq!(seconds.pack(&hlc), Ok(7 << 32 | 2));
+        assert_eq!(seconds.unpack(7 << 32 | 2), Ok(hlc));
+        // Packing would lose the milliseconds.
+        assert_eq!(
+            seconds.pack(&HybridLogicalClock::new(7001)),
+            Err(ClockError::PhysicalOutOfRange {
+                physical: 7001,
+                max: u64::from(u32::MAX) * 1000
+            })
+        );
+
+        let micros = PackedLayout::new(54, 10, TimeUnit::Microseconds);
+        assert_eq!(micros.pack(&HybridLogicalClock::new(3)), Ok(3000 << 10));
+        // Values from a finer clock are rounded down to milliseconds.
+        assert_eq!(micros.unpack(3999 << 10), Ok(HybridLogicalClock::new(3)));
+        assert_eq!(
+            micros.pack(&HybridLogicalClock::new(u64::MAX)),
+            Err(ClockError::PhysicalOutOfRange {
+                physical: u64::MAX,
+                max: micros.max_physical() / 1000
+            })
+        );
+
+        // 2^63 seconds do not fit in a u64 of milliseconds.
+        let wide = PackedLayout::new(63, 1, TimeUnit::Seconds);
+        assert!(matches!(
+            wide.unpack(u64::MAX),
+            Err(ClockError::PhysicalOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot exceed 64 bits")]
+    fn test_layout_too_wide() {
+        PackedLayout::new(48, 17, TimeUnit::Milliseconds);
+    }
+
+    /// A layout and a clock that fits in it, with a physical component that is a whole
+    /// number of the layout's unit.
+    fn layout_and_clock() -> impl Strategy<Value = (PackedLayout, HybridLogicalClock)> {
+        (1u32..=32, proptest::sample::select(&UNITS[..]))
+            .prop_flat_map(|(logical_bits, unit)| {
+                (1..=64 - logical_bits, Just(logical_bits), Just(unit))
+            })
+            .prop_flat_map(|(physical_bits, logical_bits, unit)| {
+                let layout = PackedLayout::new(physical_bits, logical_bits, unit);
+                (
+                    Just(layout),
+                    0..=layout.max_physical(),
+                    0..=layout.max_logical(),
+                )
+            })
+            .prop_filter_map("overflows milliseconds", |(layout, physical, logical)| {
+                let physical = layout.unit().to_millis(physical)?;
+                // Round to whole units, for units finer than milliseconds.
+                let physical = layout.unit().to_units(physical)?;
+                let physical = layout.unit().to_millis(physical)?;
+                Some((
+                    layout,
+                    HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                        physical, logical,
+                    ),
+                ))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip((layout, hlc) in layout_and_clock()) {
+            let packed = hlc.to_packed_u64(&layout).unwrap();
+            prop_assert_eq!(HybridLogicalClock::from_packed_u64(packed, &layout), Ok(hlc));
+        }
+
+        #[test]
+        fn prop_packed_order_matches_clock_order(
+            unit in proptest::sample::select(&UNITS[..]),
+            a in (prop_oneof![0u64..4, 0u64..1 << 40], any::<u16>()),
+            b in (prop_oneof![0u64..4, 0u64..1 << 40], any::<u16>()),
+        ) {
+            let layout = PackedLayout::new(48, 16, unit);
+            let clock = |(units, logical): (u64, u16)| {
+                HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+                    unit.to_millis(units).unwrap(),
+                    logical.into(),
+                )
+            };
+            let (a, b) = (clock(a), clock(b));
+            prop_assert_eq!(
+                a.cmp(&b),
+                a.to_packed_u64(&layout).unwrap().cmp(&b.to_packed_u64(&layout).unwrap())
+            );
+        }
+    }
+}
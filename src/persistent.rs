@@ -0,0 +1,353 @@
+use crate::{Clock, ClockError, HybridLogicalClock, PersistError, PhysicalClock, Timestamp};
+
+/// Durable storage for the high-water mark of a [`PersistentClock`].
+///
+/// The mark is a physical time that no issued timestamp has reached. Implementations must
+/// only return from [`ClockStore::store`] once the mark is durable.
+pub trait ClockStore {
+    /// The error returned when loading or storing fails.
+    type Error;
+
+    /// Loads the last stored mark, or `None` if no mark was ever stored.
+    fn load(&mut self) -> Result<Option<u64>, Self::Error>;
+
+    /// Durably replaces the stored mark with `mark`.
+    fn store(&mut self, mark: u64) -> Result<(), Self::Error>;
+}
+
+impl<S: ClockStore + ?Sized> ClockStore for &mut S {
+    type Error = S::Error;
+
+    fn load(&mut self) -> Result<Option<u64>, Self::Error> {
+        (**self).load()
+    }
+
+    fn store(&mut self, mark: u64) -> Result<(), Self::Error> {
+        (**self).store(mark)
+    }
+}
+
+/// A [`Clock`] that never issues a timestamp below one it issued before a restart.
+///
+/// Like a sequence allocator, the clock reserves a lease of physical time ahead of the
+/// timestamps it issues and persists the end of the lease as a high-water mark before
+/// issuing anything inside it. Timestamps are always below the stored mark, so a restarted
+/// clock resumes from the mark and stays ahead of everything issued before the crash, even
+/// if the wall clock stepped back. The store is written once per `window` of physical time.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "std")] {
+/// use hybrid_logical_clock::{Clock, FileStore, PersistentClock, SystemClock};
+///
+/// let store = FileStore::new("/var/lib/myservice/hlc");
+/// let mut clock = PersistentClock::open(Clock::new(SystemClock), store, 10_000)?;
+///
+/// let timestamp = clock.tick()?;
+/// # }
+/// # Ok::<(), hybrid_logical_clock::PersistError<std::io::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct PersistentClock<S, P> {
+    clock: Clock<P>,
+    store: S,
+    window: u64,
+    reserved: u64,
+}
+
+impl<S: ClockStore, P: PhysicalClock> PersistentClock<S, P> {
+    /// Recovers the high-water mark from `store` and resumes `clock` from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to persist, with its configuration and node id
+    /// * `store` - Where the high-water mark is kept
+    /// * `window` - The length of each lease of physical time
+    ///
+    /// # Returns
+    ///
+    /// The clock, or [`PersistError::Store`] if the mark cannot be loaded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn open(
+        mut clock: Clock<P>,
+        mut store: S,
+        window: u64,
+    ) -> Result<Self, PersistError<S::Error>> {
+        assert!(window > 0, "window must not be 0");
+        let reserved = store.load().map_err(PersistError::Store)?.unwrap_or(0);
+        clock.resume_from(HybridLogicalClock::new(reserved));
+        Ok(Self {
+            clock,
+            store,
+            window,
+            reserved,
+        })
+    }
+
+    /// Advances the clock for a local or send event. See [`Clock::tick`].
+    ///
+    /// # Returns
+    ///
+    /// The new timestamp, or [`PersistError::Store`] if a new lease could not be stored, in
+    /// which case no timestamp is issued.
+    pub fn tick(&mut self) -> Result<HybridLogicalClock, PersistError<S::Error>> {
+        let hlc = self.clock.tick();
+        self.issue(hlc)
+    }
+
+    /// Advances the clock and attaches its node id. See [`Clock::next_timestamp`].
+    pub fn next_timestamp(&mut self) -> Result<Timestamp, PersistError<S::Error>> {
+        let hlc = self.tick()?;
+        Ok(Timestamp::new(hlc, self.clock.node()))
+    }
+
+    /// Updates the clock based on a received timestamp. See [`Clock::update`].
+    pub fn update(
+        &mut self,
+        received: &HybridLogicalClock,
+    ) -> Result<HybridLogicalClock, PersistError<S::Error>> {
+        let hlc = self.clock.update(received);
+        self.issue(hlc)
+    }
+
+    /// Updates the clock based on a received timestamp, enforcing the configured limits.
+    /// See [`Clock::try_update`].
+    pub fn try_update(
+        &mut self,
+        received: &HybridLogicalClock,
+    ) -> Result<HybridLogicalClock, PersistError<S::Error>> {
+        let hlc = self.clock.try_update(received)?;
+        self.issue(hlc)
+    }
+
+    /// Returns the underlying clock.
+    ///
+    /// Its current value is only covered by the stored mark once it has been returned by
+    /// one of this clock's methods.
+    pub fn clock(&self) -> &Clock<P> {
+        &self.clock
+    }
+
+    /// Returns the store holding the high-water mark.
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Returns the end of the current lease: every timestamp issued so far has a smaller
+    /// physical component.
+    pub fn reserved(&self) -> u64 {
+        self.reserved
+    }
+
+    /// Stores a new lease if `hlc` is outside the current one, then returns it.
+    fn issue(
+        &mut self,
+        hlc: HybridLogicalClock,
+    ) -> Result<HybridLogicalClock, PersistError<S::Error>> {
+        if hlc.physical >= self.reserved {
+            let mark =
+                hlc.physical
+                    .checked_add(self.window)
+                    .ok_or(ClockError::PhysicalOutOfRange {
+                        physical: hlc.physical,
+                        max: u64::MAX - self.window,
+                    })?;
+            self.store.store(mark).map_err(PersistError::Store)?;
+            self.reserved = mark;
+        }
+        Ok(hlc)
+    }
+}
+
+#[cfg(feature = "std")]
+mod file {
+    use super::ClockStore;
+    use std::fs::{self, File};
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+
+    /// A [`ClockStore`] that keeps the mark in a file as 8 big-endian bytes.
+    ///
+    /// Each store writes a temporary file next to the target, syncs it, renames it over
+    /// the target and syncs the directory, so a crash leaves either the old or the new mark.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct FileStore {
+        path: PathBuf,
+    }
+
+    impl FileStore {
+        /// Creates a new FileStore keeping the mark at `path`.
+        pub fn new(path: impl Into<PathBuf>) -> Self {
+            Self { path: path.into() }
+        }
+
+        /// Returns the path of the mark file.
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn temporary_path(&self) -> PathBuf {
+            let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+            name.push(".tmp");
+            self.path.with_file_name(name)
+        }
+    }
+
+    impl ClockStore for FileStore {
+        type Error = io::Error;
+
+        fn load(&mut self) -> io::Result<Option<u64>> {
+            let bytes = match fs::read(&self.path) {
+                Ok(bytes) => bytes,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(error) => return Err(error),
+            };
+            let bytes: [u8; 8] = bytes.try_into().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData, "clock mark must be 8 bytes")
+            })?;
+            Ok(Some(u64::from_be_bytes(bytes)))
+        }
+
+        fn store(&mut self, mark: u64) -> io::Result<()> {
+            let temporary = self.temporary_path();
+            let mut file = File::create(&temporary)?;
+            file.write_all(&mark.to_be_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temporary, &self.path)?;
+            #[cfg(unix)]
+            {
+                let directory = match self.path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent,
+                    _ => Path::new("."),
+                };
+                File::open(directory)?.sync_all()?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use file::FileStore;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClockConfig, ManualClock, OffsetPolicy};
+
+    #[derive(Debug, Default)]
+    struct MemoryStore {
+        mark: Option<u64>,
+        writes: usize,
+        failing: bool,
+    }
+
+    impl ClockStore for MemoryStore {
+        type Error = &'static str;
+
+        fn load(&mut self) -> Result<Option<u64>, Self::Error> {
+            Ok(self.mark)
+        }
+
+        fn store(&mut self, mark: u64) -> Result<(), Self::Error> {
+            if self.failing {
+                return Err("disk full");
+            }
+            self.mark = Some(mark);
+            self.writes += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lease_is_stored_once_per_window() {
+        let wall = ManualClock::new(1000);
+        let mut store = MemoryStore::default();
+        let mut clock = PersistentClock::open(Clock::new(&wall), &mut store, 100).unwrap();
+
+        assert_eq!(
+            clock.tick(),
+            Ok(HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 1))
+        );
+        assert_eq!(clock.reserved(), 1100);
+        for _ in 0..99 {
+            wall.advance(1);
+            clock.tick().unwrap();
+        }
+        assert_eq!(clock.store().writes, 1);
+
+        wall.advance(1);
+        clock.tick().unwrap();
+        assert_eq!(clock.reserved(), 1200);
+        assert_eq!(store.writes, 2);
+        assert_eq!(store.mark, Some(1200));
+    }
+
+    #[test]
+    fn test_restart_after_wall_clock_stepped_back() {
+        let wall = ManualClock::new(4_000_000);
+        let mut store = MemoryStore::default();
+        let mut clock = PersistentClock::open(Clock::new(&wall), &mut store, 1000).unwrap();
+        let mut issued = clock.tick().unwrap();
+        wall.advance(10);
+        let received = HybridLogicalClock::new(4_000_500);
+        issued = issued.max(clock.update(&received).unwrap());
+
+        // The node restarts with its wall clock an hour behind.
+        wall.set(4_000_000 - 3_600_000);
+        let mut clock = PersistentClock::open(Clock::new(&wall), &mut store, 1000).unwrap();
+        let first = clock.tick().unwrap();
+        assert!(first > issued);
+        assert_eq!(first.physical, 4_001_000);
+    }
+
+    #[test]
+    fn test_errors() {
+        let wall = ManualClock::new(1000);
+        let mut store = MemoryStore {
+            failing: true,
+            ..MemoryStore::default()
+        };
+        let config = ClockConfig::new().with_max_offset(10, OffsetPolicy::Reject);
+        let mut clock =
+            PersistentClock::open(Clock::new(&wall).with_config(config), &mut store, 100).unwrap();
+
+        assert_eq!(clock.tick(), Err(PersistError::Store("disk full")));
+        assert_eq!(
+            clock.try_update(&HybridLogicalClock::new(2000)),
+            Err(PersistError::Clock(ClockError::OffsetExceeded {
+                received: 2000,
+                now: 1000,
+                max: 10,
+            }))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_file_store() {
+        let directory = std::env::temp_dir().join(std::format!(
+            "hybrid-logical-clock-{}-file-store",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut store = FileStore::new(directory.join("mark"));
+
+        assert_eq!(store.load().unwrap(), None);
+        store.store(42).unwrap();
+        store.store(u64::MAX).unwrap();
+        assert_eq!(FileStore::new(store.path()).load().unwrap(), Some(u64::MAX));
+        assert!(!directory.join("mark.tmp").exists());
+
+        std::fs::write(store.path(), b"garbage").unwrap();
+        assert_eq!(
+            store.load().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+//! Sources of physical (wall clock) time for a [`Clock`](crate::Clock).
+//!
+//! By convention every physical time in this crate is a count of milliseconds since the
+//! Unix epoch (1970-01-01T00:00:00Z). The clocks shipped here follow that convention, and
+//! custom implementations should too so that timestamps from different nodes compare
+//! meaningfully.
+
+use core::cell::Cell;
+
+/// A source of physical time.
+///
+/// Implementations return milliseconds since the Unix epoch. They do not need to be
+/// monotonic: the hybrid logical clock absorbs small backwards jumps by advancing its
+/// logical component instead.
+pub trait PhysicalClock {
+    /// Returns the current physical time in milliseconds since the Unix epoch.
+    fn now(&self) -> u64;
+}
+
+impl<T: PhysicalClock + ?Sized> PhysicalClock for &T {
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+/// A physical clock that only moves when told to, for tests and simulations.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{ManualClock, PhysicalClock};
+///
+/// let clock = ManualClock::new(1000);
+/// assert_eq!(clock.now(), 1000);
+///
+/// clock.advance(5);
+/// assert_eq!(clock.now(), 1005);
+///
+/// clock.set(900);
+/// assert_eq!(clock.now(), 900);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock {
+    now: Cell<u64>,
+}
+
+impl ManualClock {
+    /// Creates a new ManualClock reading the given time.
+    pub fn new(now: u64) -> Self {
+        Self {
+            now: Cell::new(now),
+        }
+    }
+
+    /// Sets the time returned by the clock. The time may move backwards.
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+
+    /// Moves the clock forward by `millis`.
+    pub fn advance(&self, millis: u64) {
+        self.now.set(self.now.get().saturating_add(millis));
+    }
+}
+
+impl PhysicalClock for ManualClock {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+#[cfg(feature = "std")]
+mod system {
+    use super::PhysicalClock;
+    use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+    fn millis_since_epoch(time: SystemTime) -> u64 {
+        // A system clock set before 1970 reads as the epoch itself.
+        time.duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0)
+    }
+
+    /// A physical clock backed by [`SystemTime`].
+    ///
+    /// This reads the operating system's wall clock directly, so it follows every NTP
+    /// adjustment, including steps backwards.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SystemClock;
+
+    impl PhysicalClock for SystemClock {
+        fn now(&self) -> u64 {
+            millis_since_epoch(SystemTime::now())
+        }
+    }
+
+    /// A physical clock that reads the wall clock once and then advances with [`Instant`].
+    ///
+    /// The epoch time is captured when the clock is created and every later reading adds
+    /// the monotonic time elapsed since then. The clock therefore never goes backwards, at
+    /// the cost of slowly drifting away from the wall clock if the system clock is being
+    /// corrected. Recreate it periodically if that matters.
+    #[derive(Debug, Clone, Copy)]
+    pub struct MonotonicAnchoredClock {
+        anchor_millis: u64,
+        anchor_instant: Instant,
+    }
+
+    impl MonotonicAnchoredClock {
+        /// Creates a new MonotonicAnchoredClock anchored at the current system time.
+        pub fn new() -> Self {
+            Self::anchored_at(SystemClock.now())
+        }
+
+        /// Creates a new MonotonicAnchoredClock that reads `anchor_millis` right now.
+        pub fn anchored_at(anchor_millis: u64) -> Self {
+            Self {
+                anchor_millis,
+                anchor_instant: Instant::now(),
+            }
+        }
+    }
+
+    impl Default for MonotonicAnchoredClock {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl PhysicalClock for MonotonicAnchoredClock {
+        fn now(&self) -> u64 {
+            let elapsed = self.anchor_instant.elapsed().as_millis() as u64;
+            self.anchor_millis.saturating_add(elapsed)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use system::{MonotonicAnchoredClock, SystemClock};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(10);
+        assert_eq!(clock.now(), 10);
+        clock.advance(5);
+        assert_eq!(clock.now(), 15);
+        clock.set(3);
+        assert_eq!(clock.now(), 3);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_system_clocks() {
+        // 2020-01-01T00:00:00Z, a sanity check that the unit is milliseconds.
+        const JAN_2020: u64 = 1_577_836_800_000;
+        assert!(SystemClock.now() > JAN_2020);
+
+        let anchored = MonotonicAnchoredClock::new();
+        let first = anchored.now();
+        assert!(first > JAN_2020);
+        assert!(anchored.now() >= first);
+
+        let anchored = MonotonicAnchoredClock::anchored_at(42);
+        assert!(anchored.now() >= 42);
+        assert!(anchored.now() < 42 + 60_000);
+    }
+}
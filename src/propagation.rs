@@ -0,0 +1,389 @@
+//! Carrying hybrid logical clocks across services in request headers.
+//!
+//! A clock travels either in its own `X-HLC` header or as the `hlc` entry of the W3C
+//! trace-context `tracestate` header, using the canonical text format of
+//! [`HybridLogicalClock`]. The sender injects the timestamp of the send event, and the
+//! receiver extracts it and updates its own clock with it:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use hybrid_logical_clock::{propagation, Clock, ManualClock};
+//!
+//! let mut client = Clock::new(ManualClock::new(1000));
+//! let mut headers = HashMap::new();
+//! propagation::inject(&client.tick(), &mut headers);
+//! assert_eq!(headers["x-hlc"], "1970-01-01T00:00:01.000Z-0001");
+//!
+//! let mut server = Clock::new(ManualClock::new(900));
+//! if let Some(received) = propagation::extract(&headers)? {
+//!     server.try_update(&received)?;
+//! }
+//! assert!(server.current() > client.current());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Parsing is strict: a header that is present but malformed, repeated, or out of range is
+//! an error rather than being ignored, so a broken peer is noticed instead of silently
+//! losing causality. Bounding how far ahead a received clock may be is left to
+//! [`Clock::try_update`](crate::Clock::try_update).
+//!
+//! Header names are lowercase. With the `http` feature, [`http::HeaderMap`] is a carrier
+//! too.
+//!
+//! [`http::HeaderMap`]: https://docs.rs/http/latest/http/header/struct.HeaderMap.html
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use crate::{HybridLogicalClock, ParseError};
+
+/// The name of the header [`inject`] and [`extract`] use.
+pub const HEADER: &str = "x-hlc";
+
+/// The name of the W3C trace-context header holding vendor entries.
+pub const TRACESTATE: &str = "tracestate";
+
+/// The key of the clock's entry in the `tracestate` header.
+pub const TRACESTATE_KEY: &str = "hlc";
+
+/// The longest header value accepted for a clock. Canonical values are at most 40 bytes.
+pub const MAX_VALUE_LEN: usize = 64;
+
+/// The most entries a `tracestate` header may have.
+const MAX_TRACESTATE_ENTRIES: usize = 32;
+
+/// A set of headers a clock can be written to.
+pub trait Injector {
+    /// Sets the header `name` to `value`, replacing any previous values.
+    fn set(&mut self, name: &str, value: String);
+}
+
+/// A set of headers a clock can be read from.
+pub trait Extractor {
+    /// Returns the value of the header `name`, or `None` if it is absent.
+    ///
+    /// A header that occurs more than once is returned with its values joined by commas,
+    /// as HTTP allows. Values that are not text are reported as
+    /// [`ParseError::InvalidFormat`].
+    fn get(&self, name: &str) -> Result<Option<Cow<'_, str>>, ParseError>;
+}
+
+impl Injector for BTreeMap<String, String> {
+    fn set(&mut self, name: &str, value: String) {
+        self.insert(name.to_string(), value);
+    }
+}
+
+impl Extractor for BTreeMap<String, String> {
+    fn get(&self, name: &str) -> Result<Option<Cow<'_, str>>, ParseError> {
+        Ok(BTreeMap::get(self, name).map(|value| Cow::Borrowed(value.as_str())))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: core::hash::BuildHasher> Injector for std::collections::HashMap<String, String, S> {
+    fn set(&mut self, name: &str, value: String) {
+        self.insert(name.to_string(), value);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: core::hash::BuildHasher> Extractor for std::collections::HashMap<String, String, S> {
+    fn get(&self, name: &str) -> Result<Option<Cow<'_, str>>, ParseError> {
+        Ok(std::collections::HashMap::get(self, name).map(|value| Cow::Borrowed(value.as_str())))
+    }
+}
+
+#[cfg(feature = "http")]
+impl Injector for ::http::HeaderMap {
+    fn set(&mut self, name: &str, value: String) {
+        // Names are the constants of this module and values are canonical clocks, which
+        // are always valid header names and values.
+        if let (Ok(name), Ok(value)) = (
+            ::http::header::HeaderName::try_from(name),
+            ::http::HeaderValue::try_from(value),
+        ) {
+            self.insert(name, value);
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl Extractor for ::http::HeaderMap {
+    fn get(&self, name: &str) -> Result<Option<Cow<'_, str>>, ParseError> {
+        let mut values = self
+            .get_all(name)
+            .iter()
+            .map(|value| value.to_str().map_err(|_| ParseError::InvalidFormat));
+        let Some(first) = values.next().transpose()? else {
+            return Ok(None);
+        };
+        let mut joined = Cow::Borrowed(first);
+        for value in values {
+            let joined = joined.to_mut();
+            joined.push(',');
+            joined.push_str(value?);
+        }
+        Ok(Some(joined))
+    }
+}
+
+/// Writes `hlc` to the [`HEADER`] header of `carrier`.
+pub fn inject(hlc: &HybridLogicalClock, carrier: &mut impl Injector) {
+    carrier.set(HEADER, hlc.to_string());
+}
+
+/// Reads a clock from the [`HEADER`] header of `carrier`.
+///
+/// # Returns
+///
+/// The clock, `None` if the header is absent, or an error if it is malformed, repeated or
+/// out of range. See [`parse`].
+pub fn extract(carrier: &impl Extractor) -> Result<Option<HybridLogicalClock>, ParseError> {
+    carrier.get(HEADER)?.map(|value| parse(&value)).transpose()
+}
+
+/// Writes `hlc` as the [`TRACESTATE_KEY`] entry of the `tracestate` header of `carrier`.
+///
+/// As trace context requires, the entry moves to the front and the other vendors' entries
+/// are kept after it, dropping the last ones if there would be more than 32.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use hybrid_logical_clock::{propagation, HybridLogicalClock};
+///
+/// let mut headers = BTreeMap::new();
+/// headers.insert("tracestate".to_string(), "congo=t61rcWkgMzE,hlc=stale".to_string());
+///
+/// let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1_792_324_800_123, 5);
+/// propagation::inject_tracestate(&hlc, &mut headers);
+/// assert_eq!(headers["tracestate"], "hlc=2026-10-18T12:00:00.123Z-0005,congo=t61rcWkgMzE");
+/// assert_eq!(propagation::extract_tracestate(&headers), Ok(Some(hlc)));
+/// ```
+pub fn inject_tracestate<C: Injector + Extractor>(hlc: &HybridLogicalClock, carrier: &mut C) {
+    let mut value = String::from(TRACESTATE_KEY);
+    value.push('=');
+    value.push_str(&hlc.to_string());
+
+    // The other entries belong to other vendors and are kept as they are. A header that
+    // is not even text cannot be kept and is replaced.
+    if let Some(existing) = carrier.get(TRACESTATE).ok().flatten() {
+        let others = existing
+            .split(',')
+            .map(trim)
+            .filter(|entry| !entry.is_empty())
+            .filter(|entry| entry.split_once('=').map(|(key, _)| key) != Some(TRACESTATE_KEY))
+            .take(MAX_TRACESTATE_ENTRIES - 1);
+        for entry in others {
+            value.push(',');
+            value.push_str(entry);
+        }
+    }
+    carrier.set(TRACESTATE, value);
+}
+
+/// Reads a clock from the [`TRACESTATE_KEY`] entry of the `tracestate` header of `carrier`.
+///
+/// # Returns
+///
+/// The clock, `None` if the header or the entry is absent, or an error if the entry is
+/// malformed, repeated or out of range. See [`parse_tracestate`].
+pub fn extract_tracestate(
+    carrier: &impl Extractor,
+) -> Result<Option<HybridLogicalClock>, ParseError> {
+    match carrier.get(TRACESTATE)? {
+        Some(value) => parse_tracestate(&value),
+        None => Ok(None),
+    }
+}
+
+/// Parses a header value holding a clock in the canonical text format.
+///
+/// Surrounding spaces and tabs are ignored, as in any HTTP header value. Anything else
+/// that is not exactly one canonical clock is rejected.
+///
+/// # Returns
+///
+/// The clock, [`ParseError::InvalidFormat`] if the value is malformed or longer than
+/// [`MAX_VALUE_LEN`], [`ParseError::InvalidDate`] if it names an impossible date, or
+/// [`ParseError::OutOfRange`] if it cannot be represented.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{propagation, ParseError};
+///
+/// assert!(propagation::parse(" 2026-10-18T12:00:00.123Z-0005 ").is_ok());
+/// assert_eq!(
+///     propagation::parse("2026-10-18T12:00:00.123Z-0005, 2026-10-18T12:00:00.124Z-0000"),
+///     Err(ParseError::InvalidFormat)
+/// );
+/// assert_eq!(
+///     propagation::parse("2026-10-18T12:00:00.123Z-4294967296"),
+///     Err(ParseError::OutOfRange)
+/// );
+/// ```
+pub fn parse(value: &str) -> Result<HybridLogicalClock, ParseError> {
+    let value = trim(value);
+    if value.len() > MAX_VALUE_LEN {
+        return Err(ParseError::InvalidFormat);
+    }
+    value.parse()
+}
+
+/// Parses the clock out of a `tracestate` header value.
+///
+/// # Returns
+///
+/// The clock, `None` if there is no [`TRACESTATE_KEY`] entry, or an error if an entry is
+/// not a `key=value` pair, if there is more than one clock entry, or if the clock does not
+/// [`parse`].
+pub fn parse_tracestate(value: &str) -> Result<Option<HybridLogicalClock>, ParseError> {
+    let mut found = None;
+    for entry in value.split(',').map(trim).filter(|entry| !entry.is_empty()) {
+        let (key, value) = entry.split_once('=').ok_or(ParseError::InvalidFormat)?;
+        if key != TRACESTATE_KEY {
+            continue;
+        }
+        if found.is_some() {
+            return Err(ParseError::InvalidFormat);
+        }
+        found = Some(parse(value)?);
+    }
+    Ok(found)
+}
+
+/// Strips the optional whitespace HTTP allows around values and list entries.
+fn trim(value: &str) -> &str {
+    value.trim_matches([' ', '\t'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::format;
+    use std::vec::Vec;
+
+    fn headers(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_rejects_bad_values() {
+        assert_eq!(extract(&headers(&[])), Ok(None));
+        assert_eq!(
+            extract(&headers(&[(HEADER, "2026-10-18T12:00:00.123Z-0005")])),
+            Ok(Some("2026-10-18T12:00:00.123Z-0005".parse().unwrap()))
+        );
+        for (value, error) in [
+            ("", ParseError::InvalidFormat),
+            ("garbage", ParseError::InvalidFormat),
+            ("00000000000003e800000005", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00.123Z-0005x", ParseError::InvalidFormat),
+            ("2026-10-18T12:00:00.123Z--005", ParseError::InvalidFormat),
+            ("2026-02-30T12:00:00.123Z-0005", ParseError::InvalidDate),
+            ("1969-12-31T23:59:59.999Z-0000", ParseError::OutOfRange),
+            (
+                "2026-10-18T12:00:00.123Z-4294967296",
+                ParseError::OutOfRange,
+            ),
+            (
+                "99999999999-01-01T00:00:00.000Z-0000",
+                ParseError::OutOfRange,
+            ),
+        ] {
+            assert_eq!(extract(&headers(&[(HEADER, value)])), Err(error), "{value}");
+        }
+        let padded = format!("2026-10-18T12:00:00.123Z-{:0>60}", 5);
+        assert_eq!(parse(&padded), Err(ParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_parse_tracestate() {
+        let hlc: HybridLogicalClock = "2026-10-18T12:00:00.123Z-0005".parse().unwrap();
+        assert_eq!(parse_tracestate(""), Ok(None));
+        assert_eq!(parse_tracestate("congo=t61rcWkgMzE"), Ok(None));
+        assert_eq!(
+            parse_tracestate("congo=t61rcWkgMzE ,\thlc=2026-10-18T12:00:00.123Z-0005,,"),
+            Ok(Some(hlc))
+        );
+        assert_eq!(
+            parse_tracestate("hlc=2026-10-18T12:00:00.123Z-0005,hlc=2026-10-18T12:00:00.123Z-0005"),
+            Err(ParseError::InvalidFormat)
+        );
+        assert_eq!(parse_tracestate("hlc"), Err(ParseError::InvalidFormat));
+        assert_eq!(parse_tracestate("hlc=2026"), Err(ParseError::InvalidFormat));
+    }
+
+    #[test]
+    fn test_inject_tracestate_keeps_other_vendors() {
+        let hlc = HybridLogicalClock::new(1000);
+        let mut carrier = headers(&[]);
+        inject_tracestate(&hlc, &mut carrier);
+        assert_eq!(carrier[TRACESTATE], "hlc=1970-01-01T00:00:01.000Z-0000");
+
+        let vendors: Vec<String> = (0..40).map(|i| format!("v{i}=x")).collect();
+        let joined = vendors.join(",");
+        let mut carrier = headers(&[(TRACESTATE, joined.as_str())]);
+        inject_tracestate(&hlc, &mut carrier);
+        let entries: Vec<&str> = carrier[TRACESTATE].split(',').collect();
+        assert_eq!(entries.len(), MAX_TRACESTATE_ENTRIES);
+        assert_eq!(entries[1..], vendors[..31]);
+        assert_eq!(extract_tracestate(&carrier), Ok(Some(hlc)));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_header_map() {
+        use ::http::{HeaderMap, HeaderValue};
+
+        let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 5);
+        let mut map = HeaderMap::new();
+        inject(&hlc, &mut map);
+        inject_tracestate(&hlc, &mut map);
+        assert_eq!(map["X-HLC"], "1970-01-01T00:00:01.000Z-0005");
+        assert_eq!(extract(&map), Ok(Some(hlc)));
+        assert_eq!(extract_tracestate(&map), Ok(Some(hlc)));
+
+        // Repeated tracestate headers form one list, but a repeated clock is ambiguous.
+        let mut map = HeaderMap::new();
+        map.append(TRACESTATE, HeaderValue::from_static("congo=t61rcWkgMzE"));
+        map.append(
+            TRACESTATE,
+            HeaderValue::from_static("hlc=1970-01-01T00:00:01.000Z-0005"),
+        );
+        assert_eq!(extract_tracestate(&map), Ok(Some(hlc)));
+        map.append(
+            HEADER,
+            HeaderValue::from_static("1970-01-01T00:00:01.000Z-0005"),
+        );
+        map.append(
+            HEADER,
+            HeaderValue::from_static("1970-01-01T00:00:01.000Z-0006"),
+        );
+        assert_eq!(extract(&map), Err(ParseError::InvalidFormat));
+
+        let mut map = HeaderMap::new();
+        map.insert(HEADER, HeaderValue::from_bytes(b"1970\xff").unwrap());
+        assert_eq!(extract(&map), Err(ParseError::InvalidFormat));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_inject_extract_roundtrip(physical in 0u64..=253_402_300_799_999, logical: u32) {
+            let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical);
+            let mut carrier = headers(&[(TRACESTATE, "congo=t61rcWkgMzE")]);
+            inject(&hlc, &mut carrier);
+            inject_tracestate(&hlc, &mut carrier);
+            prop_assert_eq!(extract(&carrier), Ok(Some(hlc)));
+            prop_assert_eq!(extract_tracestate(&carrier), Ok(Some(hlc)));
+        }
+    }
+}
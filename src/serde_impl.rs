@@ -0,0 +1,373 @@
+//! `serde` support for [`HybridLogicalClock`], [`Timestamp`], [`NodeId`], [`Stamped`] and
+//! [`VectorClock`](crate::vector::VectorClock).
+//!
+//! Human-readable formats such as JSON use the canonical text format, for example
+//! `"2026-10-18T12:00:00.123Z-0005"`. Binary formats use the packed `u64` of the default
+//! [`PackedLayout`], 48 bits of milliseconds and 16 bits of logical counter, and a
+//! [`Timestamp`] is that `u64` followed by its node id. Serializing a clock that does not
+//! fit in the layout to a binary format fails, so clocks that are serialized this way
+//! should cap their logical component at `u16::MAX` with
+//! [`ClockConfig::with_max_logical`](crate::ClockConfig::with_max_logical). Node ids are
+//! plain integers in both, envelopes are structs with a `timestamp` and a `payload`, and
+//! vector clocks are maps from node ids to counts.
+
+use core::fmt;
+use core::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeStruct, SerializeTuple, Serializer};
+
+use crate::{HybridLogicalClock, NodeId, PackedLayout, Stamped, TimeUnit, Timestamp};
+
+/// The layout of clocks in binary formats.
+const LAYOUT: PackedLayout = PackedLayout::new(48, 16, TimeUnit::Milliseconds);
+
+impl Serialize for HybridLogicalClock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let packed = LAYOUT.pack(self).map_err(ser::Error::custom)?;
+            serializer.serialize_u64(packed)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HybridLogicalClock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(HybridLogicalClockVisitor)
+        } else {
+            deserializer.deserialize_u64(HybridLogicalClockVisitor)
+        }
+    }
+}
+
+struct HybridLogicalClockVisitor;
+
+impl Visitor<'_> for HybridLogicalClockVisitor {
+    type Value = HybridLogicalClock;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a hybrid logical clock timestamp")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        LAYOUT.unpack(v).map_err(E::custom)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            let mut tuple = serializer.serialize_tuple(2)?;
+            tuple.serialize_element(&self.hlc())?;
+            tuple.serialize_element(&self.node)?;
+            tuple.end()
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(TimestampVisitor)
+        } else {
+            deserializer.deserialize_tuple(2, TimestampVisitor)
+        }
+    }
+}
+
+struct TimestampVisitor;
+
+impl<'de> Visitor<'de> for TimestampVisitor {
+    type Value = Timestamp;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a hybrid logical clock timestamp with a node id")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let hlc = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let node = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Timestamp::new(hlc, node))
+    }
+}
+
+impl Serialize for NodeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(NodeId)
+    }
+}
+
+const STAMPED_FIELDS: &[&str] = &["timestamp", "payload"];
+
+impl<T: Serialize> Serialize for Stamped<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut stamped = serializer.serialize_struct("Stamped", 2)?;
+        stamped.serialize_field("timestamp", &self.timestamp)?;
+        stamped.serialize_field("payload", &self.payload)?;
+        stamped.end()
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Stamped<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Stamped", STAMPED_FIELDS, StampedVisitor(PhantomData))
+    }
+}
+
+struct StampedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for StampedVisitor<T> {
+    type Value = Stamped<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a timestamp and a payload")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let timestamp = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let payload = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Stamped::from_parts(timestamp, payload))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut timestamp = None;
+        let mut payload = None;
+        while let Some(key) = map.next_key::<StampedField>()? {
+            match key {
+                StampedField::Timestamp if timestamp.is_none() => {
+                    timestamp = Some(map.next_value()?);
+                }
+                StampedField::Payload if payload.is_none() => payload = Some(map.next_value()?),
+                StampedField::Timestamp => return Err(de::Error::duplicate_field("timestamp")),
+                StampedField::Payload => return Err(de::Error::duplicate_field("payload")),
+            }
+        }
+        let timestamp = timestamp.ok_or_else(|| de::Error::missing_field("timestamp"))?;
+        let payload = payload.ok_or_else(|| de::Error::missing_field("payload"))?;
+        Ok(Stamped::from_parts(timestamp, payload))
+    }
+}
+
+enum StampedField {
+    Timestamp,
+    Payload,
+}
+
+impl<'de> Deserialize<'de> for StampedField {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(StampedFieldVisitor)
+    }
+}
+
+struct StampedFieldVisitor;
+
+impl Visitor<'_> for StampedFieldVisitor {
+    type Value = StampedField;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("`timestamp` or `payload`")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        match v {
+            "timestamp" => Ok(StampedField::Timestamp),
+            "payload" => Ok(StampedField::Payload),
+            _ => Err(de::Error::unknown_field(v, STAMPED_FIELDS)),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod vector {
+    use core::fmt;
+    use core::marker::PhantomData;
+
+    use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+
+    use crate::vector::VectorClock;
+
+    impl<N: Serialize + Ord + Clone> Serialize for VectorClock<N> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.len()))?;
+            for (node, counter) in self.iter() {
+                map.serialize_entry(node, &counter)?;
+            }
+            map.end()
+        }
+    }
+
+    impl<'de, N: Deserialize<'de> + Ord + Clone> Deserialize<'de> for VectorClock<N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_map(VectorClockVisitor(PhantomData))
+        }
+    }
+
+    struct VectorClockVisitor<N>(PhantomData<N>);
+
+    impl<'de, N: Deserialize<'de> + Ord + Clone> Visitor<'de> for VectorClockVisitor<N> {
+        type Value = VectorClock<N>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map from node ids to event counts")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut entries = alloc::vec::Vec::new();
+            while let Some(entry) = map.next_entry::<N, u64>()? {
+                entries.push(entry);
+            }
+            Ok(entries.into_iter().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_json() {
+        let hlc =
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1_792_324_800_123, 5);
+        let json = serde_json::to_string(&hlc).unwrap();
+        assert_eq!(json, r#""2026-10-18T12:00:00.123Z-0005""#);
+        assert_eq!(
+            serde_json::from_str::<HybridLogicalClock>(&json).unwrap(),
+            hlc
+        );
+
+        assert!(serde_json::from_str::<HybridLogicalClock>(r#""yesterday""#).is_err());
+        assert!(serde_json::from_str::<HybridLogicalClock>("5").is_err());
+
+        let timestamp = Timestamp::new(hlc, NodeId(7));
+        let json = serde_json::to_string(&timestamp).unwrap();
+        assert_eq!(json, r#""2026-10-18T12:00:00.123Z-0005-7""#);
+        assert_eq!(serde_json::from_str::<Timestamp>(&json).unwrap(), timestamp);
+        assert_eq!(serde_json::to_string(&NodeId(7)).unwrap(), "7");
+    }
+
+    #[test]
+    fn test_bincode() {
+        let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 5);
+        let encoded = bincode::serialize(&hlc).unwrap();
+        // The packed u64, 48 bits of milliseconds and 16 bits of logical counter.
+        assert_eq!(
+            bincode::deserialize::<u64>(&encoded).unwrap(),
+            1000 << 16 | 5
+        );
+        assert_eq!(
+            bincode::deserialize::<HybridLogicalClock>(&encoded).unwrap(),
+            hlc
+        );
+
+        let timestamp = Timestamp::new(hlc, NodeId(7));
+        let encoded = bincode::serialize(&timestamp).unwrap();
+        assert_eq!(
+            bincode::deserialize::<(u64, u64)>(&encoded).unwrap(),
+            (1000 << 16 | 5, 7)
+        );
+        assert_eq!(
+            bincode::deserialize::<Timestamp>(&encoded).unwrap(),
+            timestamp
+        );
+
+        // Clocks that do not fit in the layout cannot be serialized.
+        let busy = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 65536);
+        assert!(bincode::serialize(&busy).is_err());
+        assert!(bincode::serialize(&HybridLogicalClock::new(1 << 48)).is_err());
+    }
+
+    #[test]
+    fn test_stamped() {
+        use crate::{Clock, ManualClock};
+
+        let mut alice = Clock::new(ManualClock::new(1_792_324_800_123)).with_node(NodeId(7));
+        let mut bob = Clock::new(ManualClock::new(1000)).with_node(NodeId(8));
+        let message = alice.stamp("hello");
+
+        let json = serde_json::to_string(&message).unwrap();
+        assert_eq!(
+            json,
+            r#"{"timestamp":"2026-10-18T12:00:00.123Z-0001-7","payload":"hello"}"#
+        );
+        let decoded: Stamped<std::string::String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(bob.receive(decoded).unwrap(), "hello");
+        assert!(bob.current() > message.timestamp().hlc());
+        assert!(serde_json::from_str::<Stamped<u8>>(r#"{"payload":1}"#).is_err());
+
+        let binary = bincode::serialize(&message).unwrap();
+        let decoded: Stamped<std::string::String> = bincode::deserialize(&binary).unwrap();
+        assert_eq!(decoded.timestamp(), message.timestamp());
+        assert_eq!(bob.receive(decoded).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_vector_clock() {
+        use crate::vector::VectorClock;
+
+        let clock: VectorClock = [(NodeId(1), 3), (NodeId(7), 1)].into_iter().collect();
+        let json = serde_json::to_string(&clock).unwrap();
+        assert_eq!(json, r#"{"1":3,"7":1}"#);
+        assert_eq!(serde_json::from_str::<VectorClock>(&json).unwrap(), clock);
+
+        let binary = bincode::serialize(&clock).unwrap();
+        assert_eq!(bincode::deserialize::<VectorClock>(&binary).unwrap(), clock);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(
+            physical in 0u64..1 << 48,
+            logical in 0..=u32::from(u16::MAX),
+            node in any::<u64>(),
+        ) {
+            let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical);
+            let json = serde_json::to_vec(&hlc).unwrap();
+            prop_assert_eq!(serde_json::from_slice::<HybridLogicalClock>(&json).unwrap(), hlc);
+            let binary = bincode::serialize(&hlc).unwrap();
+            prop_assert_eq!(bincode::deserialize::<HybridLogicalClock>(&binary).unwrap(), hlc);
+
+            let timestamp = Timestamp::new(hlc, NodeId(node));
+            let json = serde_json::to_vec(&timestamp).unwrap();
+            prop_assert_eq!(serde_json::from_slice::<Timestamp>(&json).unwrap(), timestamp);
+            let binary = bincode::serialize(&timestamp).unwrap();
+            prop_assert_eq!(bincode::deserialize::<Timestamp>(&binary).unwrap(), timestamp);
+        }
+
+        #[test]
+        fn prop_json_round_trip(physical in any::<u64>(), logical in any::<u32>()) {
+            let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical);
+            let json = serde_json::to_vec(&hlc).unwrap();
+            prop_assert_eq!(serde_json::from_slice::<HybridLogicalClock>(&json).unwrap(), hlc);
+        }
+    }
+}
@@ -0,0 +1,644 @@
+//! A deterministic simulation harness for protocols built on hybrid logical clocks.
+//!
+//! A [`Simulation`] runs any number of nodes on virtual time. Each node reads a simulated
+//! wall clock described by a [`ClockModel`] (skew, drift and jumps), and messages travel
+//! over a virtual network with random delays, which reorders them, and partitions, which
+//! drop them. Every tick, send, receive and drop is recorded in a trace that
+//! [`Simulation::check_invariants`] verifies. All randomness comes from a [`SimRng`]
+//! seeded by the caller, so a failing run can be replayed from its seed.
+//!
+//! Requires the `sim` feature.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{HybridLogicalClock, NodeId};
+
+/// A small, seeded pseudo-random number generator (SplitMix64).
+///
+/// It is fast and reproducible, not cryptographically secure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimRng {
+    state: u64,
+}
+
+impl SimRng {
+    /// Creates a new SimRng from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next pseudo-random `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a pseudo-random value in `low..=high`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `low` is greater than `high`.
+    pub fn between(&mut self, low: u64, high: u64) -> u64 {
+        assert!(low <= high, "low must not be greater than high");
+        match (high - low).checked_add(1) {
+            Some(span) => low + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+
+    /// Returns true with a probability of `numerator / denominator`.
+    pub fn ratio(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator.max(1) < numerator
+    }
+}
+
+/// How a simulated node's wall clock deviates from true time.
+///
+/// The wall clock reads `time + skew + time * drift_ppm / 1_000_000` plus every jump that
+/// has happened by `time`, floored at 0.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClockModel {
+    skew: i64,
+    drift_ppm: i64,
+    jumps: Vec<(u64, i64)>,
+}
+
+impl ClockModel {
+    /// Creates a new ClockModel that follows true time exactly.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offsets the wall clock by `skew` milliseconds.
+    pub fn with_skew(mut self, skew: i64) -> Self {
+        self.skew = skew;
+        self
+    }
+
+    /// Makes the wall clock run `drift_ppm` parts per million fast, or slow if negative.
+    pub fn with_drift_ppm(mut self, drift_ppm: i64) -> Self {
+        self.drift_ppm = drift_ppm;
+        self
+    }
+
+    /// Makes the wall clock jump by `delta` milliseconds at true time `at`, like an NTP
+    /// step. Negative jumps move the wall clock backwards.
+    pub fn with_jump(mut self, at: u64, delta: i64) -> Self {
+        self.jumps.push((at, delta));
+        self
+    }
+
+    /// Returns the wall clock reading at true time `time`.
+    pub fn wall(&self, time: u64) -> u64 {
+        let time = i128::from(time);
+        let jumps: i128 = self
+            .jumps
+            .iter()
+            .filter(|(at, _)| i128::from(*at) <= time)
+            .map(|(_, delta)| i128::from(*delta))
+            .sum();
+        let wall = time + i128::from(self.skew) + time * i128::from(self.drift_ppm) / 1_000_000;
+        (wall + jumps).clamp(0, i128::from(u64::MAX)) as u64
+    }
+}
+
+/// What happened in a [`TraceEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceKind {
+    /// A local event.
+    Tick,
+    /// A message was sent.
+    Send {
+        /// The destination node.
+        to: NodeId,
+        /// The message's sequence number.
+        message: u64,
+    },
+    /// A message was received and absorbed.
+    Receive {
+        /// The sending node.
+        from: NodeId,
+        /// The message's sequence number.
+        message: u64,
+        /// The timestamp the message was sent with.
+        sent: HybridLogicalClock,
+    },
+    /// A message was dropped because its sender and destination were partitioned.
+    Drop {
+        /// The sending node.
+        from: NodeId,
+        /// The message's sequence number.
+        message: u64,
+    },
+}
+
+/// One entry of a simulation trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// The true time of the event.
+    pub time: u64,
+    /// The node the event happened on.
+    pub node: NodeId,
+    /// The node's wall clock reading.
+    pub wall: u64,
+    /// The node's hybrid logical clock after the event.
+    pub hlc: HybridLogicalClock,
+    /// What happened.
+    pub kind: TraceKind,
+}
+
+/// An invariant violated by a simulation trace, with the index of the offending event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// A node's clock did not advance past its previous value.
+    NotMonotonic {
+        /// The index of the event in the trace.
+        index: usize,
+    },
+    /// A received message's timestamp was not smaller than the receiver's clock.
+    CausalityViolated {
+        /// The index of the receive event in the trace.
+        index: usize,
+    },
+    /// A node's clock was further ahead of its wall clock than allowed.
+    DivergenceExceeded {
+        /// The index of the event in the trace.
+        index: usize,
+        /// How far the clock's physical component was ahead of the wall clock.
+        divergence: u64,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::NotMonotonic { index } => {
+                write!(f, "event {index}: clock did not advance")
+            }
+            Violation::CausalityViolated { index } => {
+                write!(
+                    f,
+                    "event {index}: received timestamp is not before the receive"
+                )
+            }
+            Violation::DivergenceExceeded { index, divergence } => {
+                write!(
+                    f,
+                    "event {index}: clock is {divergence} ahead of the wall clock"
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Violation {}
+
+/// A message delivered by [`Simulation::step`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery<M> {
+    /// The sending node.
+    pub from: NodeId,
+    /// The receiving node.
+    pub to: NodeId,
+    /// The timestamp the message was sent with.
+    pub sent: HybridLogicalClock,
+    /// The receiver's clock after absorbing the message.
+    pub received: HybridLogicalClock,
+    /// The message.
+    pub payload: M,
+}
+
+#[derive(Debug, Clone)]
+struct SimNode {
+    model: ClockModel,
+    hlc: HybridLogicalClock,
+    group: u32,
+}
+
+#[derive(Debug, Clone)]
+struct InFlight<M> {
+    from: NodeId,
+    to: NodeId,
+    sent: HybridLogicalClock,
+    payload: M,
+}
+
+/// A deterministic network of simulated nodes exchanging messages of type `M`.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::sim::{ClockModel, Simulation};
+///
+/// let mut sim = Simulation::new(42).with_delay(1, 20);
+/// let fast = sim.add_node(ClockModel::new().with_skew(50));
+/// let slow = sim.add_node(ClockModel::new().with_drift_ppm(-200));
+///
+/// sim.send(fast, slow, "ping");
+/// sim.advance(5);
+/// sim.tick(slow);
+///
+/// let delivery = sim.step().unwrap();
+/// assert_eq!(delivery.payload, "ping");
+/// assert!(delivery.received > delivery.sent);
+/// assert_eq!(sim.check_invariants(50), Ok(()));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulation<M> {
+    rng: SimRng,
+    time: u64,
+    min_delay: u64,
+    max_delay: u64,
+    nodes: Vec<SimNode>,
+    in_flight: BTreeMap<(u64, u64), InFlight<M>>,
+    next_message: u64,
+    next_group: u32,
+    trace: Vec<TraceEvent>,
+}
+
+impl<M> Simulation<M> {
+    /// Creates a new Simulation with no nodes at true time 0, where messages take between
+    /// 1 and 10 milliseconds.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SimRng::new(seed),
+            time: 0,
+            min_delay: 1,
+            max_delay: 10,
+            nodes: Vec::new(),
+            in_flight: BTreeMap::new(),
+            next_message: 0,
+            next_group: 1,
+            trace: Vec::new(),
+        }
+    }
+
+    /// Sets the range of message delays in milliseconds. Delays are drawn uniformly from
+    /// `min..=max`, so messages are reordered when the range allows it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than `max`.
+    pub fn with_delay(mut self, min: u64, max: u64) -> Self {
+        assert!(min <= max, "min must not be greater than max");
+        self.min_delay = min;
+        self.max_delay = max;
+        self
+    }
+
+    /// Adds a node whose wall clock follows `model`, with a clock starting at its current
+    /// wall time.
+    ///
+    /// # Returns
+    ///
+    /// The id of the new node. Nodes are numbered from 0 in the order they are added.
+    pub fn add_node(&mut self, model: ClockModel) -> NodeId {
+        let hlc = HybridLogicalClock::new(model.wall(self.time));
+        self.nodes.push(SimNode {
+            model,
+            hlc,
+            group: 0,
+        });
+        NodeId(self.nodes.len() as u64 - 1)
+    }
+
+    /// Returns the true time.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Returns the current wall clock reading of `node`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` was not added to the simulation.
+    pub fn wall(&self, node: NodeId) -> u64 {
+        self.node(node).model.wall(self.time)
+    }
+
+    /// Returns the current clock of `node`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` was not added to the simulation.
+    pub fn hlc(&self, node: NodeId) -> HybridLogicalClock {
+        self.node(node).hlc
+    }
+
+    /// Returns the random number generator, so workloads can draw from the same seed.
+    pub fn rng(&mut self) -> &mut SimRng {
+        &mut self.rng
+    }
+
+    /// Moves true time forward by `millis`.
+    ///
+    /// Messages that fall due in the meantime are delivered by later calls to
+    /// [`Simulation::step`], as if they had been delayed further.
+    pub fn advance(&mut self, millis: u64) {
+        self.time = self.time.saturating_add(millis);
+    }
+
+    /// Records a local event on `node`.
+    ///
+    /// # Returns
+    ///
+    /// The node's new clock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` was not added to the simulation.
+    pub fn tick(&mut self, node: NodeId) -> HybridLogicalClock {
+        self.local(node, TraceKind::Tick)
+    }
+
+    /// Sends `payload` from `from` to `to`, ticking the sender's clock.
+    ///
+    /// # Returns
+    ///
+    /// The timestamp the message was sent with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `from` or `to` was not added to the simulation.
+    pub fn send(&mut self, from: NodeId, to: NodeId, payload: M) -> HybridLogicalClock {
+        self.node(to);
+        let message = self.next_message;
+        self.next_message += 1;
+        let sent = self.local(from, TraceKind::Send { to, message });
+        let delay = self.rng.between(self.min_delay, self.max_delay);
+        let due = self.time.saturating_add(delay);
+        self.in_flight.insert(
+            (due, message),
+            InFlight {
+                from,
+                to,
+                sent,
+                payload,
+            },
+        );
+        sent
+    }
+
+    /// Delivers the next message that is due, moving true time forward to its delivery
+    /// time if needed. Messages between partitioned nodes are dropped along the way.
+    ///
+    /// # Returns
+    ///
+    /// The delivered message, or `None` once no messages are in flight.
+    pub fn step(&mut self) -> Option<Delivery<M>> {
+        loop {
+            let ((due, message), in_flight) = self.in_flight.pop_first()?;
+            self.time = self.time.max(due);
+            let to = in_flight.to;
+            let wall = self.wall(to);
+            let connected = self.node(in_flight.from).group == self.node(to).group;
+            let receiver = &mut self.nodes[to.0 as usize];
+            let kind = if connected {
+                receiver.hlc.update(&in_flight.sent, wall);
+                TraceKind::Receive {
+                    from: in_flight.from,
+                    message,
+                    sent: in_flight.sent,
+                }
+            } else {
+                TraceKind::Drop {
+                    from: in_flight.from,
+                    message,
+                }
+            };
+            let received = receiver.hlc;
+            self.record(to, wall, received, kind);
+            if connected {
+                return Some(Delivery {
+                    from: in_flight.from,
+                    to,
+                    sent: in_flight.sent,
+                    received,
+                    payload: in_flight.payload,
+                });
+            }
+        }
+    }
+
+    /// Returns the number of messages in flight.
+    pub fn pending(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Cuts `side` off from every other node. Messages between the two sides are dropped
+    /// when they fall due, until [`Simulation::heal`] is called.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node in `side` was not added to the simulation. No node is moved then.
+    pub fn partition(&mut self, side: &[NodeId]) {
+        for &node in side {
+            self.node(node);
+        }
+        let group = self.next_group;
+        self.next_group += 1;
+        for node in side {
+            self.nodes[node.0 as usize].group = group;
+        }
+    }
+
+    /// Removes every partition.
+    pub fn heal(&mut self) {
+        for node in &mut self.nodes {
+            node.group = 0;
+        }
+    }
+
+    /// Returns every tick, send, receive and drop so far, in the order they happened.
+    pub fn trace(&self) -> &[TraceEvent] {
+        &self.trace
+    }
+
+    /// Checks the trace against the guarantees of hybrid logical clocks:
+    ///
+    /// * every tick, send and receive advances the node's clock,
+    /// * every received timestamp is smaller than the clock after the receive, and
+    /// * no clock's physical component is more than `max_divergence` ahead of its node's
+    ///   wall clock. This holds when `max_divergence` bounds how far wall clocks are apart.
+    ///
+    /// # Returns
+    ///
+    /// The first violation in the trace, if any.
+    pub fn check_invariants(&self, max_divergence: u64) -> Result<(), Violation> {
+        let mut last: BTreeMap<NodeId, HybridLogicalClock> = BTreeMap::new();
+        for (index, event) in self.trace.iter().enumerate() {
+            if matches!(event.kind, TraceKind::Drop { .. }) {
+                continue;
+            }
+            if let Some(previous) = last.insert(event.node, event.hlc) {
+                if event.hlc <= previous {
+                    return Err(Violation::NotMonotonic { index });
+                }
+            }
+            if let TraceKind::Receive { sent, .. } = event.kind {
+                if sent >= event.hlc {
+                    return Err(Violation::CausalityViolated { index });
+                }
+            }
+            let divergence = event.hlc.physical.saturating_sub(event.wall);
+            if divergence > max_divergence {
+                return Err(Violation::DivergenceExceeded { index, divergence });
+            }
+        }
+        Ok(())
+    }
+
+    fn node(&self, node: NodeId) -> &SimNode {
+        self.nodes
+            .get(node.0 as usize)
+            .unwrap_or_else(|| panic!("unknown node {node}"))
+    }
+
+    fn local(&mut self, node: NodeId, kind: TraceKind) -> HybridLogicalClock {
+        let wall = self.wall(node);
+        let hlc = self.nodes[node.0 as usize].hlc.tick(wall);
+        self.record(node, wall, hlc, kind);
+        hlc
+    }
+
+    fn record(&mut self, node: NodeId, wall: u64, hlc: HybridLogicalClock, kind: TraceKind) {
+        self.trace.push(TraceEvent {
+            time: self.time,
+            node,
+            wall,
+            hlc,
+            kind,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_clock_model() {
+        let model = ClockModel::new()
+            .with_skew(-100)
+            .with_drift_ppm(1000)
+            .with_jump(5000, 20)
+            .with_jump(8000, -50);
+        assert_eq!(model.wall(0), 0);
+        assert_eq!(model.wall(1000), 901);
+        assert_eq!(model.wall(5000), 4925);
+        assert_eq!(model.wall(8000), 7878);
+    }
+
+    #[test]
+    fn test_partition_drops_messages() {
+        let mut sim = Simulation::new(7);
+        let a = sim.add_node(ClockModel::new());
+        let b = sim.add_node(ClockModel::new());
+
+        sim.partition(&[a]);
+        sim.send(a, b, 1);
+        assert_eq!(sim.step(), None);
+        assert!(matches!(
+            sim.trace().last().unwrap().kind,
+            TraceKind::Drop { message: 0, .. }
+        ));
+
+        sim.heal();
+        sim.send(b, a, 2);
+        assert_eq!(sim.step().map(|delivery| delivery.payload), Some(2));
+        assert_eq!(sim.pending(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown node 5")]
+    fn test_partition_rejects_unknown_nodes() {
+        let mut sim = Simulation::<()>::new(7);
+        let a = sim.add_node(ClockModel::new());
+        sim.partition(&[a, NodeId(5)]);
+    }
+
+    #[test]
+    fn test_backward_jump_is_reported() {
+        let mut sim = Simulation::<()>::new(0);
+        let node = sim.add_node(ClockModel::new().with_skew(2000).with_jump(100, -1000));
+        sim.advance(50);
+        assert_eq!(sim.tick(node), HybridLogicalClock::new(2050));
+        sim.advance(150);
+        sim.tick(node);
+        assert_eq!(
+            sim.check_invariants(100),
+            Err(Violation::DivergenceExceeded {
+                index: 1,
+                divergence: 850,
+            })
+        );
+        assert_eq!(sim.check_invariants(850), Ok(()));
+    }
+
+    /// Runs a random gossip workload where wall clocks are skewed by up to 50 ms either way,
+    /// drift by up to 100 ppm over at most 10 s, and jump forward by up to 30 ms once.
+    fn gossip(seed: u64) -> Simulation<u32> {
+        let mut sim = Simulation::new(seed).with_delay(0, 50);
+        for _ in 0..4 {
+            let rng = sim.rng();
+            let skew = rng.between(0, 100) as i64 - 50;
+            let drift = rng.between(0, 200) as i64 - 100;
+            let jump = (rng.between(0, 10_000), rng.between(0, 30) as i64);
+            sim.add_node(
+                ClockModel::new()
+                    .with_skew(skew)
+                    .with_drift_ppm(drift)
+                    .with_jump(jump.0, jump.1),
+            );
+        }
+        for round in 0..200 {
+            let rng = sim.rng();
+            let from = NodeId(rng.between(0, 3));
+            let to = NodeId(rng.between(0, 3));
+            let advance = rng.between(0, 50);
+            let partitioned = rng.ratio(1, 20);
+            let healed = rng.ratio(1, 5);
+            match round % 3 {
+                0 => {
+                    sim.tick(from);
+                }
+                1 => {
+                    sim.send(from, to, round);
+                }
+                _ => {
+                    sim.step();
+                }
+            }
+            if partitioned {
+                sim.partition(&[from]);
+            } else if healed {
+                sim.heal();
+            }
+            sim.advance(advance);
+        }
+        while sim.step().is_some() {}
+        sim
+    }
+
+    proptest! {
+        #[test]
+        fn prop_invariants_hold(seed in any::<u64>()) {
+            let sim = gossip(seed);
+            // Wall clocks are at most 100 + 2 + 30 ms apart.
+            prop_assert_eq!(sim.check_invariants(132), Ok(()));
+        }
+
+        #[test]
+        fn prop_runs_are_reproducible(seed in any::<u64>()) {
+            let (first, second) = (gossip(seed), gossip(seed));
+            prop_assert_eq!(first.trace(), second.trace());
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{HybridLogicalClock, NodeId};
+
+/// A callback notified with the new watermark whenever it advances.
+type Subscriber = Box<dyn FnMut(HybridLogicalClock) + Send>;
+
+/// Tracks the latest clock value each peer has acknowledged and computes the stable
+/// watermark: the smallest of them, which every peer has seen.
+///
+/// Anything at or below the watermark is known cluster-wide, so tombstones below it can be
+/// collected (see [`LwwMap::gc`](crate::crdt::LwwMap::gc)) and transactions below it can be
+/// finalized. The watermark never moves backwards.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{HybridLogicalClock, NodeId, StabilityTracker};
+/// use std::sync::mpsc;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let mut tracker = StabilityTracker::new();
+/// tracker.subscribe(move |watermark| sender.send(watermark).unwrap());
+///
+/// tracker.join(NodeId(1), HybridLogicalClock::new(100));
+/// tracker.join(NodeId(2), HybridLogicalClock::new(200));
+/// assert_eq!(tracker.watermark(), Some(HybridLogicalClock::new(100)));
+///
+/// tracker.record(&NodeId(1), HybridLogicalClock::new(300));
+/// assert_eq!(tracker.watermark(), Some(HybridLogicalClock::new(200)));
+/// assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [
+///     HybridLogicalClock::new(100),
+///     HybridLogicalClock::new(200),
+/// ]);
+/// ```
+pub struct StabilityTracker<N = NodeId> {
+    peers: BTreeMap<N, HybridLogicalClock>,
+    watermark: Option<HybridLogicalClock>,
+    subscribers: Vec<Subscriber>,
+}
+
+impl<N: Ord> StabilityTracker<N> {
+    /// Creates a new StabilityTracker with no peers and no watermark.
+    pub fn new() -> Self {
+        Self {
+            peers: BTreeMap::new(),
+            watermark: None,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Registers a callback that receives the new watermark every time it advances.
+    pub fn subscribe(&mut self, subscriber: impl FnMut(HybridLogicalClock) + Send + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    /// Adds a peer that has seen everything up to `hlc`.
+    ///
+    /// A new peer is assumed to have been brought up to date from a replica, so it starts at
+    /// least at the current watermark. Joining a peer that is already a member records
+    /// `hlc` for it instead.
+    pub fn join(&mut self, peer: N, hlc: HybridLogicalClock) {
+        let hlc = self.watermark.map_or(hlc, |watermark| watermark.max(hlc));
+        let seen = self.peers.entry(peer).or_insert(hlc);
+        *seen = (*seen).max(hlc);
+        self.refresh();
+    }
+
+    /// Removes a peer, which can advance the watermark if it was the slowest.
+    ///
+    /// # Returns
+    ///
+    /// The last clock value the peer acknowledged, or `None` if it was not a member.
+    pub fn leave(&mut self, peer: &N) -> Option<HybridLogicalClock> {
+        let seen = self.peers.remove(peer)?;
+        self.refresh();
+        Some(seen)
+    }
+
+    /// Records that `peer` has seen everything up to `hlc`.
+    ///
+    /// Acknowledgements older than the peer's latest one are ignored.
+    ///
+    /// # Returns
+    ///
+    /// False if `peer` is not a member, in which case nothing is recorded.
+    pub fn record(&mut self, peer: &N, hlc: HybridLogicalClock) -> bool {
+        let Some(seen) = self.peers.get_mut(peer) else {
+            return false;
+        };
+        if hlc > *seen {
+            *seen = hlc;
+            self.refresh();
+        }
+        true
+    }
+
+    /// Returns the stable watermark, or `None` if no peer has joined yet.
+    ///
+    /// When the last peer leaves, the watermark stays where it was.
+    pub fn watermark(&self) -> Option<HybridLogicalClock> {
+        self.watermark
+    }
+
+    /// Returns the latest clock value `peer` acknowledged.
+    pub fn last_seen(&self, peer: &N) -> Option<HybridLogicalClock> {
+        self.peers.get(peer).copied()
+    }
+
+    /// Returns the peers and the latest clock values they acknowledged, in peer order.
+    pub fn peers(&self) -> impl Iterator<Item = (&N, HybridLogicalClock)> {
+        self.peers.iter().map(|(peer, hlc)| (peer, *hlc))
+    }
+
+    /// Returns the number of peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Returns true if there are no peers.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Recomputes the watermark and notifies the subscribers if it advanced.
+    fn refresh(&mut self) {
+        let Some(minimum) = self.peers.values().min().copied() else {
+            return;
+        };
+        if self.watermark < Some(minimum) {
+            self.watermark = Some(minimum);
+            for subscriber in &mut self.subscribers {
+                subscriber(minimum);
+            }
+        }
+    }
+}
+
+impl<N: Ord> Default for StabilityTracker<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: fmt::Debug> fmt::Debug for StabilityTracker<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StabilityTracker")
+            .field("peers", &self.peers)
+            .field("watermark", &self.watermark)
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    fn at(physical: u64) -> HybridLogicalClock {
+        HybridLogicalClock::new(physical)
+    }
+
+    #[test]
+    fn test_join_and_leave() {
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let mut tracker = StabilityTracker::new();
+        let sink = Arc::clone(&notified);
+        tracker.subscribe(move |watermark| sink.lock().unwrap().push(watermark));
+
+        assert!(!tracker.record(&NodeId(1), at(10)));
+        assert_eq!(tracker.watermark(), None);
+
+        tracker.join(NodeId(1), at(10));
+        tracker.join(NodeId(2), at(20));
+        assert_eq!(tracker.watermark(), Some(at(10)));
+
+        // Stale acknowledgements are ignored.
+        assert!(tracker.record(&NodeId(2), at(5)));
+        assert_eq!(tracker.last_seen(&NodeId(2)), Some(at(20)));
+
+        // The slowest peer leaving advances the watermark.
+        assert_eq!(tracker.leave(&NodeId(1)), Some(at(10)));
+        assert_eq!(tracker.watermark(), Some(at(20)));
+
+        // A peer joining behind the watermark starts at the watermark.
+        tracker.join(NodeId(3), at(1));
+        assert_eq!(tracker.last_seen(&NodeId(3)), Some(at(20)));
+
+        assert_eq!(tracker.leave(&NodeId(2)), Some(at(20)));
+        assert_eq!(tracker.leave(&NodeId(3)), Some(at(20)));
+        assert!(tracker.is_empty());
+        assert_eq!(tracker.watermark(), Some(at(20)));
+
+        assert_eq!(*notified.lock().unwrap(), [at(10), at(20)]);
+    }
+
+    #[derive(Debug, Clone)]
+    enum Step {
+        Join(u8, u64),
+        Leave(u8),
+        Record(u8, u64),
+    }
+
+    proptest! {
+        #[test]
+        fn prop_watermark_is_monotonic_minimum(
+            steps in proptest::collection::vec(
+                prop_oneof![
+                    (0u8..4, 0u64..100).prop_map(|(peer, physical)| Step::Join(peer, physical)),
+                    (0u8..4).prop_map(Step::Leave),
+                    (0u8..4, 0u64..100).prop_map(|(peer, physical)| Step::Record(peer, physical)),
+                ],
+                0..40,
+            )
+        ) {
+            let notified = Arc::new(Mutex::new(Vec::new()));
+            let mut tracker = StabilityTracker::new();
+            let sink = Arc::clone(&notified);
+            tracker.subscribe(move |watermark| sink.lock().unwrap().push(watermark));
+
+            let mut previous = None;
+            for step in steps {
+                match step {
+                    Step::Join(peer, physical) => tracker.join(peer, at(physical)),
+                    Step::Leave(peer) => {
+                        tracker.leave(&peer);
+                    }
+                    Step::Record(peer, physical) => {
+                        tracker.record(&peer, at(physical));
+                    }
+                }
+
+                let watermark = tracker.watermark();
+                prop_assert!(watermark >= previous);
+                if let Some(minimum) = tracker.peers().map(|(_, hlc)| hlc).min() {
+                    prop_assert_eq!(watermark, Some(minimum));
+                }
+                if watermark != previous {
+                    prop_assert_eq!(notified.lock().unwrap().last().copied(), watermark);
+                }
+                previous = watermark;
+            }
+        }
+    }
+}
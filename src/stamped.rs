@@ -0,0 +1,146 @@
+use crate::{Clock, ClockError, PhysicalClock, Timestamp};
+
+/// A message payload together with the timestamp of its send event.
+///
+/// Envelopes are created by [`Clock::stamp`] and opened by [`Clock::receive`], which
+/// updates the receiving clock before handing out the payload. Since there is no other way
+/// to take the payload out of an envelope, a receiver cannot forget to update its clock.
+///
+/// With the `serde` feature, envelopes serialize as their timestamp and payload, and
+/// deserialize back into an envelope, so a payload read from the wire still has to go
+/// through [`Clock::receive`].
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{Clock, ManualClock, NodeId};
+///
+/// let mut alice = Clock::new(ManualClock::new(5000)).with_node(NodeId(1));
+/// let mut bob = Clock::new(ManualClock::new(1000)).with_node(NodeId(2));
+///
+/// let message = alice.stamp("hello");
+/// assert_eq!(bob.receive(message), Ok("hello"));
+/// assert!(bob.current() > alice.current());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stamped<T> {
+    pub(crate) timestamp: Timestamp,
+    pub(crate) payload: T,
+}
+
+impl<T> Stamped<T> {
+    /// Assembles an envelope from a timestamp and a payload, for example when relaying a
+    /// message whose timestamp was issued elsewhere.
+    pub fn from_parts(timestamp: Timestamp, payload: T) -> Self {
+        Self { timestamp, payload }
+    }
+
+    /// Returns the timestamp of the send event.
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
+impl<P: PhysicalClock> Clock<P> {
+    /// Ticks the clock for a send event and wraps `payload` with the new timestamp.
+    ///
+    /// See [`Clock::next_timestamp`].
+    pub fn stamp<T>(&mut self, payload: T) -> Stamped<T> {
+        Stamped {
+            timestamp: self.next_timestamp(),
+            payload,
+        }
+    }
+
+    /// Updates the clock with the timestamp of a received envelope and returns its
+    /// payload.
+    ///
+    /// The update goes through [`Clock::try_update_from`], so the configured offset and
+    /// logical limits apply and the sender's offset is recorded in [`Clock::stats`].
+    ///
+    /// # Returns
+    ///
+    /// The payload, or the error from [`Clock::try_update`] if the envelope was rejected, in
+    /// which case the clock is unchanged. An envelope accepted under
+    /// [`OffsetPolicy::AcceptAndReport`](crate::OffsetPolicy::AcceptAndReport) returns its
+    /// payload, and the violation is counted in [`Clock::stats`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{Clock, ClockConfig, ClockError, ManualClock, OffsetPolicy};
+    ///
+    /// let mut ahead = Clock::new(ManualClock::new(86_400_000));
+    /// let config = ClockConfig::new().with_max_offset(100, OffsetPolicy::Reject);
+    /// let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+    ///
+    /// let message = ahead.stamp("from the future");
+    /// assert!(matches!(clock.receive(message), Err(ClockError::OffsetExceeded { .. })));
+    /// ```
+    pub fn receive<T>(&mut self, stamped: Stamped<T>) -> Result<T, ClockError> {
+        self.try_update_from(&stamped.timestamp)?;
+        Ok(stamped.payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClockConfig, HybridLogicalClock, ManualClock, NodeId, OffsetPolicy};
+
+    #[test]
+    fn test_round_trip() {
+        let wall = ManualClock::new(1000);
+        let mut alice = Clock::new(&wall).with_node(NodeId(1));
+        let mut bob = Clock::new(&wall).with_node(NodeId(2));
+
+        let first = alice.stamp(1);
+        assert_eq!(first.timestamp().node, NodeId(1));
+        assert_eq!(first.timestamp().hlc(), alice.current());
+
+        // A reassembled envelope is received like the original.
+        let copy = Stamped::from_parts(first.timestamp(), 1);
+        assert_eq!(copy, first);
+        assert_eq!(bob.receive(first), Ok(1));
+        assert!(bob.current() > copy.timestamp().hlc());
+
+        let reply = bob.stamp(2);
+        assert_eq!(alice.receive(reply.clone()), Ok(2));
+        assert!(alice.current() > reply.timestamp().hlc());
+    }
+
+    #[test]
+    fn test_rejected_envelope_leaves_clock_unchanged() {
+        let config = ClockConfig::new().with_max_offset(10, OffsetPolicy::Reject);
+        let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+        let message =
+            Stamped::from_parts(Timestamp::new(HybridLogicalClock::new(2000), NodeId(9)), ());
+
+        assert_eq!(
+            clock.receive(message),
+            Err(ClockError::OffsetExceeded {
+                received: 2000,
+                now: 1000,
+                max: 10,
+            })
+        );
+        assert_eq!(clock.current(), HybridLogicalClock::new(1000));
+    }
+
+    #[test]
+    fn test_accepted_envelope_keeps_payload() {
+        let config = ClockConfig::new().with_max_offset(10, OffsetPolicy::AcceptAndReport);
+        let mut clock = Clock::new(ManualClock::new(1000)).with_config(config);
+        let message = Stamped::from_parts(
+            Timestamp::new(HybridLogicalClock::new(2000), NodeId(9)),
+            "late but kept",
+        );
+
+        assert_eq!(clock.receive(message), Ok("late but kept"));
+        assert_eq!(
+            clock.current(),
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(2000, 1)
+        );
+        assert_eq!(clock.stats().offset_violations(), 1);
+    }
+}
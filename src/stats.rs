@@ -0,0 +1,305 @@
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+
+use crate::{ClockError, HybridLogicalClock, NodeId};
+
+/// Running statistics of observed clock offsets: how far received physical times were
+/// ahead of the local physical time, in the clock's unit. Negative offsets mean the
+/// received time was behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkewStats {
+    count: u64,
+    last: i64,
+    min: i64,
+    max: i64,
+    ewma: f64,
+}
+
+impl SkewStats {
+    /// The weight of the newest observation in the moving average.
+    pub const EWMA_WEIGHT: f64 = 0.125;
+
+    fn new(offset: i64) -> Self {
+        Self {
+            count: 1,
+            last: offset,
+            min: offset,
+            max: offset,
+            ewma: offset as f64,
+        }
+    }
+
+    fn observe(&mut self, offset: i64) {
+        self.count += 1;
+        self.last = offset;
+        self.min = self.min.min(offset);
+        self.max = self.max.max(offset);
+        self.ewma += Self::EWMA_WEIGHT * (offset as f64 - self.ewma);
+    }
+
+    /// Returns the number of observations.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the most recent offset.
+    pub fn last(&self) -> i64 {
+        self.last
+    }
+
+    /// Returns the smallest offset observed.
+    pub fn min(&self) -> i64 {
+        self.min
+    }
+
+    /// Returns the largest offset observed.
+    pub fn max(&self) -> i64 {
+        self.max
+    }
+
+    /// Returns the exponentially weighted moving average of the offsets, where each new
+    /// observation has a weight of [`SkewStats::EWMA_WEIGHT`].
+    pub fn ewma(&self) -> f64 {
+        self.ewma
+    }
+}
+
+/// A snapshot of what a [`Clock`](crate::Clock) has observed, for monitoring clock health.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{Clock, HybridLogicalClock, ManualClock, NodeId, Timestamp};
+///
+/// let mut clock = Clock::new(ManualClock::new(1000));
+/// let received = Timestamp::new(HybridLogicalClock::new(1250), NodeId(3));
+/// clock.update_from(&received);
+/// clock.tick();
+///
+/// let stats = clock.stats();
+/// assert_eq!(stats.events(), 2);
+/// // The wall clock is behind the received time, so both timestamps counted logically.
+/// assert_eq!(stats.logical_bumps(), 2);
+/// assert_eq!(stats.skew().unwrap().max(), 250);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClockStats {
+    events: u64,
+    logical_bumps: u64,
+    max_logical: u32,
+    rejected_updates: u64,
+    offset_violations: u64,
+    last_offset_violation: Option<ClockError>,
+    skew: Option<SkewStats>,
+    #[cfg(feature = "alloc")]
+    peers: BTreeMap<NodeId, SkewStats>,
+}
+
+impl ClockStats {
+    /// Returns the number of timestamps the clock has issued.
+    pub fn events(&self) -> u64 {
+        self.events
+    }
+
+    /// Returns how many issued timestamps had a non-zero logical component, because the
+    /// physical time did not advance past the clock.
+    pub fn logical_bumps(&self) -> u64 {
+        self.logical_bumps
+    }
+
+    /// Returns the largest logical component the clock has issued.
+    pub fn max_logical(&self) -> u32 {
+        self.max_logical
+    }
+
+    /// Returns how many received timestamps the checked updates rejected without moving
+    /// the clock.
+    pub fn rejected_updates(&self) -> u64 {
+        self.rejected_updates
+    }
+
+    /// Returns how many received timestamps exceeded the maximum offset but were absorbed
+    /// anyway under [`OffsetPolicy::AcceptAndReport`](crate::OffsetPolicy::AcceptAndReport).
+    pub fn offset_violations(&self) -> u64 {
+        self.offset_violations
+    }
+
+    /// Returns the most recent violation counted by [`ClockStats::offset_violations`].
+    pub fn last_offset_violation(&self) -> Option<ClockError> {
+        self.last_offset_violation
+    }
+
+    /// Returns the offsets observed across all received timestamps, or `None` if nothing
+    /// has been received.
+    pub fn skew(&self) -> Option<&SkewStats> {
+        self.skew.as_ref()
+    }
+
+    /// Returns the offsets observed in timestamps received from `peer`.
+    ///
+    /// Only accepted updates that name their sender, such as
+    /// [`Clock::update_from`](crate::Clock::update_from), are attributed to a peer, and at
+    /// most [`ClockConfig::max_peers`](crate::ClockConfig::max_peers) peers are tracked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{Clock, HybridLogicalClock, ManualClock, NodeId, Timestamp};
+    ///
+    /// let mut clock = Clock::new(ManualClock::new(1000));
+    /// clock.update_from(&Timestamp::new(HybridLogicalClock::new(1250), NodeId(3)));
+    /// clock.update(&HybridLogicalClock::new(1100));
+    ///
+    /// assert_eq!(clock.stats().peer_skew(&NodeId(3)).unwrap().last(), 250);
+    /// assert_eq!(clock.stats().skew().unwrap().count(), 2);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn peer_skew(&self, peer: &NodeId) -> Option<&SkewStats> {
+        self.peers.get(peer)
+    }
+
+    /// Returns the offsets observed for every peer, in node order.
+    #[cfg(feature = "alloc")]
+    pub fn peers(&self) -> impl Iterator<Item = (NodeId, &SkewStats)> {
+        self.peers.iter().map(|(peer, stats)| (*peer, stats))
+    }
+
+    /// Records a timestamp issued by the clock when the local physical time was `now`.
+    pub(crate) fn record_event(&mut self, hlc: HybridLogicalClock, now: u64) {
+        self.events += 1;
+        if hlc.logical > 0 {
+            self.logical_bumps += 1;
+        }
+        self.max_logical = self.max_logical.max(hlc.logical);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_event(hlc, offset(hlc.physical, now));
+        #[cfg(not(feature = "metrics"))]
+        let _ = now;
+    }
+
+    /// Records a received timestamp that was rejected.
+    pub(crate) fn record_rejection(&mut self, error: &ClockError) {
+        self.rejected_updates += 1;
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_rejection(error);
+        #[cfg(not(feature = "metrics"))]
+        let _ = error;
+    }
+
+    /// Records a received timestamp that exceeded the maximum offset but was absorbed.
+    pub(crate) fn record_offset_violation(&mut self, error: ClockError) {
+        self.offset_violations += 1;
+        self.last_offset_violation = Some(error);
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_offset_violation();
+    }
+
+    /// Records the offset of a received physical time from the local one, and attributes
+    /// it to `peer` unless `max_peers` other peers are already tracked.
+    ///
+    /// Pass a peer only for accepted timestamps, so rejected senders are never tracked.
+    ///
+    /// # Returns
+    ///
+    /// The offset, saturated to the range of `i64`.
+    pub(crate) fn record_offset(
+        &mut self,
+        peer: Option<NodeId>,
+        received: u64,
+        now: u64,
+        max_peers: usize,
+    ) -> i64 {
+        let offset = offset(received, now);
+        match &mut self.skew {
+            Some(skew) => skew.observe(offset),
+            None => self.skew = Some(SkewStats::new(offset)),
+        }
+        let tracked = peer.filter(|peer| self.record_peer_offset(*peer, offset, max_peers));
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_offset(tracked, offset);
+        #[cfg(not(feature = "metrics"))]
+        let _ = tracked;
+        offset
+    }
+
+    /// Attributes `offset` to `peer` if it is tracked or there is room to track it.
+    ///
+    /// # Returns
+    ///
+    /// Whether `peer` is tracked.
+    #[cfg(feature = "alloc")]
+    fn record_peer_offset(&mut self, peer: NodeId, offset: i64, max_peers: usize) -> bool {
+        if let Some(skew) = self.peers.get_mut(&peer) {
+            skew.observe(offset);
+        } else if self.peers.len() < max_peers {
+            self.peers.insert(peer, SkewStats::new(offset));
+        } else {
+            return false;
+        }
+        true
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn record_peer_offset(&mut self, _peer: NodeId, _offset: i64, _max_peers: usize) -> bool {
+        false
+    }
+}
+
+/// Returns how far `time` is ahead of `now`, saturated to the range of `i64`.
+fn offset(time: u64, now: u64) -> i64 {
+    (i128::from(time) - i128::from(now)).clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skew_stats() {
+        let mut stats = ClockStats::default();
+        assert_eq!(stats.skew(), None);
+
+        stats.record_offset(Some(NodeId(1)), 1100, 1000, 1);
+        stats.record_offset(None, 900, 1000, 1);
+        assert_eq!(
+            stats.record_offset(Some(NodeId(1)), u64::MAX, 0, 1),
+            i64::MAX
+        );
+
+        let skew = stats.skew().unwrap();
+        assert_eq!((skew.count(), skew.min(), skew.max()), (3, -100, i64::MAX));
+        assert_eq!(skew.last(), i64::MAX);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_peer_skew_stats() {
+        let mut stats = ClockStats::default();
+        stats.record_offset(Some(NodeId(1)), 1100, 1000, 1);
+        stats.record_offset(None, 900, 1000, 1);
+        stats.record_offset(Some(NodeId(1)), u64::MAX, 0, 1);
+
+        let peer = stats.peer_skew(&NodeId(1)).unwrap();
+        assert_eq!(peer.count(), 2);
+        assert_eq!(peer.min(), 100);
+        assert!(peer.ewma() > 100.0);
+        assert_eq!(stats.peers().count(), 1);
+
+        // Peers beyond the limit are not tracked, but tracked peers still are.
+        assert!(!stats.record_peer_offset(NodeId(2), 5, 1));
+        assert_eq!(stats.peer_skew(&NodeId(2)), None);
+        assert!(stats.record_peer_offset(NodeId(1), 5, 1));
+        assert_eq!(stats.peer_skew(&NodeId(1)).unwrap().count(), 3);
+        assert_eq!(stats.skew().unwrap().count(), 3);
+    }
+
+    #[test]
+    fn test_ewma_converges() {
+        let mut skew = SkewStats::new(0);
+        for _ in 0..100 {
+            skew.observe(80);
+        }
+        assert!((skew.ewma() - 80.0).abs() < 0.01);
+        assert_eq!(skew.min(), 0);
+    }
+}
@@ -0,0 +1,319 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::format::parse_digits;
+use crate::{ClockError, HybridLogicalClock, ParseError};
+
+/// Identifies the node that issued a [`Timestamp`].
+///
+/// Node ids only need to be unique within a cluster. They break ties between timestamps
+/// with equal physical and logical components, so their order carries no meaning beyond
+/// being deterministic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub u64);
+
+impl From<u16> for NodeId {
+    fn from(id: u16) -> Self {
+        Self(id.into())
+    }
+}
+
+impl From<u32> for NodeId {
+    fn from(id: u32) -> Self {
+        Self(id.into())
+    }
+}
+
+impl From<u64> for NodeId {
+    fn from(id: u64) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for NodeId {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_digits(s).map(Self)
+    }
+}
+
+/// A hybrid logical clock value together with the node that issued it.
+///
+/// Two nodes can issue timestamps with identical physical and logical components, which
+/// [`HybridLogicalClock`] considers equal. `Timestamp` breaks those ties by node id, so
+/// timestamps from different nodes are never equal and form a true total order. This is
+/// what makes last-writer-wins merges deterministic.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{HybridLogicalClock, NodeId, Timestamp};
+///
+/// let hlc = HybridLogicalClock::new(1000);
+/// let a = Timestamp::new(hlc, NodeId(1));
+/// let b = Timestamp::new(hlc, NodeId(2));
+///
+/// assert_eq!(a.hlc(), b.hlc());
+/// assert!(a < b);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    /// The physical component of the clock.
+    pub physical: u64,
+    /// The logical component of the clock.
+    pub logical: u32,
+    /// The node that issued the timestamp.
+    pub node: NodeId,
+}
+
+impl Timestamp {
+    /// The length of the binary encoding produced by [`Timestamp::to_be_bytes`].
+    pub const ENCODED_LEN: usize = HybridLogicalClock::ENCODED_LEN_WITH_NODE;
+
+    /// Creates a new Timestamp from a clock value and the node that issued it.
+    pub fn new(hlc: HybridLogicalClock, node: NodeId) -> Self {
+        Self {
+            physical: hlc.physical,
+            logical: hlc.logical,
+            node,
+        }
+    }
+
+    /// Returns the clock value without the node id.
+    pub fn hlc(&self) -> HybridLogicalClock {
+        HybridLogicalClock::new_with_both_physical_and_logical_clock_time(
+            self.physical,
+            self.logical,
+        )
+    }
+
+    /// Encodes the timestamp as 20 big-endian bytes that sort in the same order as the
+    /// timestamps.
+    ///
+    /// See [`HybridLogicalClock::to_be_bytes_with_node`].
+    pub fn to_be_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        self.hlc().to_be_bytes_with_node(self.node.0)
+    }
+
+    /// Decodes a timestamp from the encoding produced by [`Timestamp::to_be_bytes`].
+    pub fn from_be_bytes(bytes: [u8; Self::ENCODED_LEN]) -> Self {
+        let (hlc, node) = HybridLogicalClock::from_be_bytes_with_node(bytes);
+        Self::new(hlc, NodeId(node))
+    }
+
+    /// Writes the encoding produced by [`Timestamp::to_be_bytes`] to the start of `buf`
+    /// without allocating.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written, or [`ClockError::BufferTooSmall`] if `buf` is shorter
+    /// than [`Timestamp::ENCODED_LEN`].
+    pub fn write_to(&self, buf: &mut [u8]) -> Result<usize, ClockError> {
+        let actual = buf.len();
+        let buf = buf
+            .get_mut(..Self::ENCODED_LEN)
+            .ok_or(ClockError::BufferTooSmall {
+                needed: Self::ENCODED_LEN,
+                actual,
+            })?;
+        buf.copy_from_slice(&self.to_be_bytes());
+        Ok(Self::ENCODED_LEN)
+    }
+
+    /// Reads a timestamp from the start of `buf`, as written by [`Timestamp::write_to`].
+    ///
+    /// # Returns
+    ///
+    /// The decoded timestamp, or [`ClockError::BufferTooSmall`] if `buf` is shorter than
+    /// [`Timestamp::ENCODED_LEN`].
+    pub fn read_from(buf: &[u8]) -> Result<Self, ClockError> {
+        let bytes = buf
+            .get(..Self::ENCODED_LEN)
+            .ok_or(ClockError::BufferTooSmall {
+                needed: Self::ENCODED_LEN,
+                actual: buf.len(),
+            })?;
+        Ok(Self::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Parses the compact hex format produced by [`LowerHex`](fmt::LowerHex): the 24 hex
+    /// digits of the clock followed by the 16 hex digits of the node id.
+    pub fn from_hex(s: &str) -> Result<Self, ParseError> {
+        if s.len() != 40 || !s.is_char_boundary(24) {
+            return Err(ParseError::InvalidFormat);
+        }
+        let (hlc, node) = s.split_at(24);
+        if !node.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseError::InvalidFormat);
+        }
+        let node = u64::from_str_radix(node, 16).map_err(|_| ParseError::InvalidFormat)?;
+        Ok(Self::new(HybridLogicalClock::from_hex(hlc)?, NodeId(node)))
+    }
+}
+
+impl From<Timestamp> for HybridLogicalClock {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.hlc()
+    }
+}
+
+impl fmt::Display for Timestamp {
+    /// Formats the timestamp as the canonical text format of the clock followed by the
+    /// node id.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::{HybridLogicalClock, NodeId, Timestamp};
+    ///
+    /// let hlc = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1_792_324_800_123, 5);
+    /// let timestamp = Timestamp::new(hlc, NodeId(7));
+    ///
+    /// assert_eq!(timestamp.to_string(), "2026-10-18T12:00:00.123Z-0005-7");
+    /// assert_eq!(timestamp.to_string().parse(), Ok(timestamp));
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.hlc(), self.node)
+    }
+}
+
+impl fmt::LowerHex for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}{:016x}", self.hlc(), self.node.0)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hlc, node) = s.rsplit_once('-').ok_or(ParseError::InvalidFormat)?;
+        Ok(Self::new(hlc.parse()?, node.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crdt::LwwRegister;
+    use crate::{Clock, ManualClock};
+    use proptest::prelude::*;
+    use std::format;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    fn timestamp(physical: u64, logical: u32, node: u64) -> Timestamp {
+        Timestamp::new(
+            HybridLogicalClock::new_with_both_physical_and_logical_clock_time(physical, logical),
+            NodeId(node),
+        )
+    }
+
+    #[test]
+    fn test_ordering_breaks_ties_by_node() {
+        assert!(timestamp(100, 5, 9) < timestamp(100, 6, 1));
+        assert!(timestamp(100, 5, 1) < timestamp(100, 5, 2));
+        assert!(timestamp(99, 9, 9) < timestamp(100, 0, 0));
+    }
+
+    #[test]
+    fn test_clocks_on_different_nodes_issue_distinct_timestamps() {
+        let wall = ManualClock::new(1000);
+        let mut a = Clock::new(&wall).with_node(NodeId(1));
+        let mut b = Clock::new(&wall).with_node(NodeId(2));
+        let from_a = a.next_timestamp();
+        let from_b = b.next_timestamp();
+        assert_eq!(from_a.hlc(), from_b.hlc());
+        assert_ne!(from_a, from_b);
+        assert_eq!(from_a.node, a.node());
+    }
+
+    #[test]
+    fn test_text_formats() {
+        let ts = timestamp(1000, 5, 7);
+        assert_eq!(ts.to_string(), "1970-01-01T00:00:01.000Z-0005-7");
+        assert_eq!(
+            format!("{ts:x}"),
+            "00000000000003e8000000050000000000000007"
+        );
+        for input in [
+            "1970-01-01T00:00:01.000Z-0005",
+            "1970-01-01T00:00:01.000Z-0005-",
+            "1970-01-01T00:00:01.000Z-0005-x7",
+            "1970-01-01T00:00:01.000Z-0005-+7",
+        ] {
+            assert!(input.parse::<Timestamp>().is_err(), "parsing {input:?}");
+        }
+        assert_eq!(
+            Timestamp::from_hex("00000000000003e80000000500000000000000+7"),
+            Err(ParseError::InvalidFormat)
+        );
+    }
+
+    fn timestamp_strategy() -> impl Strategy<Value = Timestamp> {
+        // A narrow range of clocks makes ties on the clock, and so on node ids, common.
+        (0u64..3, 0u32..3, 0u64..4).prop_map(|(p, l, n)| timestamp(p, l, n))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trip(physical in any::<u64>(), logical in any::<u32>(), node in any::<u64>()) {
+            let ts = timestamp(physical, logical, node);
+            prop_assert_eq!(ts.to_string().parse(), Ok(ts));
+            prop_assert_eq!(Timestamp::from_hex(&format!("{ts:x}")), Ok(ts));
+            prop_assert_eq!(Timestamp::from_be_bytes(ts.to_be_bytes()), ts);
+            let mut buf = [0u8; Timestamp::ENCODED_LEN];
+            prop_assert_eq!(ts.write_to(&mut buf), Ok(Timestamp::ENCODED_LEN));
+            prop_assert_eq!(Timestamp::read_from(&buf), Ok(ts));
+        }
+
+        #[test]
+        fn prop_byte_order_matches_timestamp_order(a in timestamp_strategy(), b in timestamp_strategy()) {
+            prop_assert_eq!(a.cmp(&b), a.to_be_bytes().cmp(&b.to_be_bytes()));
+        }
+
+        #[test]
+        fn prop_merges_converge_regardless_of_delivery_order(
+            steps in proptest::collection::vec((0usize..3, 0u64..2), 1..16),
+            rotation in any::<prop::sample::Index>(),
+        ) {
+            // Clocks on three nodes with the same wall time, so their clock values often tie.
+            let mut clocks: Vec<_> = (0..3)
+                .map(|node| Clock::new(ManualClock::new(1000)).with_node(NodeId(node)))
+                .collect();
+            let writes: Vec<_> = steps
+                .iter()
+                .enumerate()
+                .map(|(value, &(node, millis))| {
+                    clocks[node].physical_clock().advance(millis);
+                    LwwRegister::new(value, clocks[node].next_timestamp())
+                })
+                .collect();
+
+            let mut reversed = writes.clone();
+            reversed.reverse();
+            let mut rotated = writes.clone();
+            rotated.rotate_left(rotation.index(writes.len()));
+            // Redelivering every write changes nothing.
+            rotated.extend(writes.iter().cloned());
+
+            let replay = |order: &[LwwRegister<usize>]| {
+                let mut replica = order[0].clone();
+                for write in order {
+                    replica.merge(write);
+                }
+                replica
+            };
+            let replica = replay(&writes);
+            prop_assert_eq!(&replay(&reversed), &replica);
+            prop_assert_eq!(&replay(&rotated), &replica);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Hybrid logical clock timestamps in [`tracing`](https://docs.rs/tracing) spans and events.
+//!
+//! Logs collected from many machines are usually sorted by their wall clock time, which
+//! puts an event before its cause whenever the machines' clocks disagree. Stamping every
+//! event with a tick of a shared [`AtomicHlc`] instead, and updating that clock from the
+//! timestamps carried by incoming requests, makes the stamps respect causality.
+//!
+//! `tracing::Value` is sealed, so it cannot be implemented outside of `tracing`. Record a
+//! [`HybridLogicalClock`] with its [`Display`](core::fmt::Display) implementation instead,
+//! as in `tracing::info!(hlc = %hlc)`, or with [`record`].
+//!
+//! # Example
+//!
+//! ```
+//! use std::sync::Arc;
+//! use hybrid_logical_clock::{tracing::layer, AtomicHlc, HybridLogicalClock, SystemClock};
+//! use tracing_subscriber::layer::SubscriberExt;
+//!
+//! let clock = Arc::new(AtomicHlc::new(HybridLogicalClock::new(0)).unwrap());
+//! let subscriber = tracing_subscriber::registry().with(layer(clock.clone(), SystemClock));
+//!
+//! tracing::subscriber::with_default(subscriber, || {
+//!     // Printed as `2026-10-18T12:00:00.123Z-0000  INFO ...`.
+//!     tracing::info!("request handled");
+//! });
+//! ```
+
+use ::tracing::field::display;
+use ::tracing::{Span, Subscriber};
+use core::fmt;
+use std::sync::Arc;
+use tracing_subscriber::fmt::format::{DefaultFields, Format, Full, Writer};
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::{AtomicHlc, HybridLogicalClock, PhysicalClock};
+
+/// The name of the span field [`record`] writes to.
+pub const FIELD: &str = "hlc";
+
+/// Records `hlc` in the [`FIELD`] field of `span`.
+///
+/// Like any span field, it must be declared when the span is created, for example as
+/// `tracing::info_span!("request", hlc = tracing::field::Empty)`.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::{Clock, ManualClock};
+/// use tracing::field::Empty;
+///
+/// let mut clock = Clock::new(ManualClock::new(1000));
+/// let span = tracing::info_span!("request", hlc = Empty);
+/// hybrid_logical_clock::tracing::record(&span, clock.tick());
+/// ```
+pub fn record(span: &Span, hlc: HybridLogicalClock) {
+    span.record(FIELD, display(hlc));
+}
+
+/// A [`FormatTime`] that stamps every formatted event with a tick of a shared clock instead
+/// of the wall clock time.
+///
+/// Use it with [`tracing_subscriber::fmt::Layer::with_timer`], or build a whole layer with
+/// [`layer`]. Stamps are written with the canonical text format, so they sort in clock order.
+#[derive(Debug, Clone)]
+pub struct HlcTimer<P> {
+    clock: Arc<AtomicHlc>,
+    physical_clock: P,
+}
+
+impl<P: PhysicalClock> HlcTimer<P> {
+    /// Creates a new HlcTimer that ticks `clock` with the time read from `physical_clock`.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to tick, shared with the code that receives timestamps
+    /// * `physical_clock` - The source of physical time for each tick
+    pub fn new(clock: Arc<AtomicHlc>, physical_clock: P) -> Self {
+        Self {
+            clock,
+            physical_clock,
+        }
+    }
+
+    /// Returns the clock this timer ticks.
+    pub fn clock(&self) -> &Arc<AtomicHlc> {
+        &self.clock
+    }
+
+    /// Ticks the clock for an event. If the clock has run out of physical bits, the event
+    /// gets the last timestamp instead of none.
+    fn stamp(&self) -> HybridLogicalClock {
+        self.clock
+            .tick(self.physical_clock.now())
+            .unwrap_or_else(|_| self.clock.load())
+    }
+}
+
+impl<P: PhysicalClock> FormatTime for HlcTimer<P> {
+    fn format_time(&self, w: &mut Writer<'_>) -> fmt::Result {
+        write!(w, "{}", self.stamp())
+    }
+}
+
+/// Returns a [`tracing_subscriber::fmt::Layer`] that stamps every event with a tick of
+/// `clock` in place of the wall clock time.
+///
+/// The layer can be configured further like any formatting layer, for example with
+/// `with_writer`.
+///
+/// # Arguments
+///
+/// * `clock` - The clock to tick, shared with the code that receives timestamps
+/// * `physical_clock` - The source of physical time for each tick
+pub fn layer<S, P>(
+    clock: Arc<AtomicHlc>,
+    physical_clock: P,
+) -> tracing_subscriber::fmt::Layer<S, DefaultFields, Format<Full, HlcTimer<P>>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    P: PhysicalClock,
+{
+    tracing_subscriber::fmt::layer().with_timer(HlcTimer::new(clock, physical_clock))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::string::String;
+    use std::sync::Mutex;
+    use std::vec::Vec;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Debug, Clone)]
+    struct Frozen(u64);
+
+    impl PhysicalClock for Frozen {
+        fn now(&self) -> u64 {
+            self.0
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_layer_stamps_events() {
+        let clock = Arc::new(AtomicHlc::new(HybridLogicalClock::new(1000)).unwrap());
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(layer(clock.clone(), Frozen(1000)).with_writer(move || writer.clone()));
+
+        ::tracing::subscriber::with_default(subscriber, || {
+            ::tracing::info!("first");
+            // A request from a node with a faster clock moves the stamps ahead.
+            let received = HybridLogicalClock::new(5000);
+            clock.update(&received, 1000).unwrap();
+            let span = ::tracing::info_span!("request", hlc = ::tracing::field::Empty);
+            record(&span, received);
+            span.in_scope(|| ::tracing::info!("second"));
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let first = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(1000, 1);
+        let second = HybridLogicalClock::new_with_both_physical_and_logical_clock_time(5000, 2);
+        assert!(lines[0].starts_with(&std::format!("{first} ")));
+        assert!(lines[0].ends_with("first"));
+        assert!(lines[1].starts_with(&std::format!("{second} ")));
+        assert!(lines[1].contains(&std::format!(
+            "request{{hlc={}}}",
+            HybridLogicalClock::new(5000)
+        )));
+        assert_eq!(clock.load(), second);
+    }
+
+    #[test]
+    fn test_timer_falls_back_when_clock_is_exhausted() {
+        let layout = crate::PackedLayout::new(4, 1, crate::TimeUnit::Milliseconds);
+        let last = HybridLogicalClock::new(15);
+        let timer = HlcTimer::new(
+            Arc::new(AtomicHlc::with_layout(last, layout).unwrap()),
+            Frozen(99),
+        );
+        assert_eq!(timer.stamp(), last);
+    }
+}
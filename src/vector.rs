@@ -0,0 +1,551 @@
+//! Vector clocks, for detecting concurrent updates.
+//!
+//! A [`VectorClock`] counts the events each node has contributed to a causal history.
+//! Unlike hybrid logical clock timestamps, vector clocks can tell concurrent events apart
+//! from causally related ones, at the cost of growing with the number of nodes. An
+//! [`HlcVector`] pairs one with a [`HybridLogicalClock`] so events still carry a physical
+//! time and a total order.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::{ClockError, HybridLogicalClock, NodeId, Timestamp};
+
+/// A vector clock mapping node ids to event counts.
+///
+/// Nodes without an entry have a count of 0, and zero counts are never stored, so two
+/// vector clocks are equal exactly when they describe the same history.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::NodeId;
+/// use hybrid_logical_clock::vector::VectorClock;
+///
+/// let mut a = VectorClock::new();
+/// a.increment(NodeId(1));
+///
+/// let mut b = a.clone();
+/// b.increment(NodeId(2));
+/// assert!(a < b);
+///
+/// a.increment(NodeId(1));
+/// assert_eq!(a.partial_cmp(&b), None);
+///
+/// a.merge(&b);
+/// assert!(b < a);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VectorClock<N = NodeId> {
+    entries: BTreeMap<N, u64>,
+}
+
+impl<N: Ord + Clone> VectorClock<N> {
+    /// Creates a new, empty VectorClock.
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the number of events from `node`.
+    pub fn get(&self, node: &N) -> u64 {
+        self.entries.get(node).copied().unwrap_or(0)
+    }
+
+    /// Records a new event on `node` and returns its new count.
+    pub fn increment(&mut self, node: N) -> u64 {
+        let counter = self.entries.entry(node).or_insert(0);
+        *counter += 1;
+        *counter
+    }
+
+    /// Merges `other` into this clock by taking the larger count for every node.
+    pub fn merge(&mut self, other: &Self) {
+        for (node, &counter) in &other.entries {
+            let entry = self.entries.entry(node.clone()).or_insert(0);
+            *entry = (*entry).max(counter);
+        }
+    }
+
+    /// Raises the count for `node` to at least `counter`.
+    pub(crate) fn observe(&mut self, node: N, counter: u64) {
+        if counter > 0 {
+            let entry = self.entries.entry(node).or_insert(0);
+            *entry = (*entry).max(counter);
+        }
+    }
+
+    /// Returns true if this clock happened before `other`.
+    pub fn happened_before(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Less)
+    }
+
+    /// Returns true if neither clock happened before the other.
+    pub fn concurrent_with(&self, other: &Self) -> bool {
+        self.partial_cmp(other).is_none()
+    }
+
+    /// Removes the entry for `node` and returns its count.
+    ///
+    /// See [`VectorClock::retain`] for when this is safe.
+    pub fn remove(&mut self, node: &N) -> u64 {
+        self.entries.remove(node).unwrap_or(0)
+    }
+
+    /// Keeps only the entries for which `keep` returns true.
+    ///
+    /// Pruning forgets history, so comparisons with clocks that still have the removed
+    /// entries become meaningless. Only prune nodes that have left the cluster once every
+    /// replica has seen all of their events.
+    pub fn retain(&mut self, mut keep: impl FnMut(&N, u64) -> bool) {
+        self.entries.retain(|node, counter| keep(node, *counter));
+    }
+
+    /// Returns the nodes with at least one event and their counts, in node order.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, u64)> {
+        self.entries.iter().map(|(node, &counter)| (node, counter))
+    }
+
+    /// Returns the number of nodes with at least one event.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no events have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns true if every count in this clock is at most the matching count in `other`.
+    fn dominated_by(&self, other: &Self) -> bool {
+        self.entries
+            .iter()
+            .all(|(node, &counter)| counter <= other.get(node))
+    }
+}
+
+impl<N: Ord + Clone> Default for VectorClock<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Ord + Clone> PartialOrd for VectorClock<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.dominated_by(other), other.dominated_by(self)) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+impl<N: Ord + Clone> FromIterator<(N, u64)> for VectorClock<N> {
+    fn from_iter<I: IntoIterator<Item = (N, u64)>>(iter: I) -> Self {
+        let mut clock = Self::new();
+        for (node, counter) in iter {
+            if counter > 0 {
+                let entry = clock.entries.entry(node).or_insert(0);
+                *entry = (*entry).max(counter);
+            }
+        }
+        clock
+    }
+}
+
+impl VectorClock<NodeId> {
+    /// The length of the encoding of one entry: a node id and a count, both big-endian `u64`s.
+    const ENTRY_LEN: usize = 16;
+
+    /// Encodes the clock as a big-endian `u32` entry count followed by each node id and
+    /// count as big-endian `u64`s, in node order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use hybrid_logical_clock::NodeId;
+    /// use hybrid_logical_clock::vector::VectorClock;
+    ///
+    /// let clock: VectorClock = [(NodeId(1), 3), (NodeId(2), 1)].into_iter().collect();
+    /// let bytes = clock.to_bytes();
+    ///
+    /// assert_eq!(bytes.len(), 4 + 2 * 16);
+    /// assert_eq!(VectorClock::from_bytes(&bytes), Ok(clock));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + self.len() * Self::ENTRY_LEN);
+        bytes.extend_from_slice(&(self.len() as u32).to_be_bytes());
+        for (node, counter) in self.iter() {
+            bytes.extend_from_slice(&node.0.to_be_bytes());
+            bytes.extend_from_slice(&counter.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a clock from the encoding produced by [`VectorClock::to_bytes`].
+    ///
+    /// # Returns
+    ///
+    /// The decoded clock, [`ClockError::BufferTooSmall`] if `bytes` is truncated, or
+    /// [`ClockError::InvalidEncoding`] if it has trailing bytes, entries out of order, or
+    /// zero counts.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ClockError> {
+        let (len, mut rest) = bytes
+            .split_first_chunk::<4>()
+            .ok_or(ClockError::BufferTooSmall {
+                needed: 4,
+                actual: bytes.len(),
+            })?;
+        let len = u32::from_be_bytes(*len) as usize;
+        let needed = len
+            .checked_mul(Self::ENTRY_LEN)
+            .and_then(|entries| entries.checked_add(4))
+            .ok_or(ClockError::InvalidEncoding)?;
+        if bytes.len() < needed {
+            return Err(ClockError::BufferTooSmall {
+                needed,
+                actual: bytes.len(),
+            });
+        }
+        if bytes.len() > needed {
+            return Err(ClockError::InvalidEncoding);
+        }
+
+        let mut clock = Self::new();
+        let mut previous = None;
+        while let Some((entry, remaining)) = rest.split_first_chunk::<{ Self::ENTRY_LEN }>() {
+            let (node, counter) = entry.split_at(8);
+            let node = NodeId(u64::from_be_bytes(node.try_into().unwrap()));
+            let counter = u64::from_be_bytes(counter.try_into().unwrap());
+            if counter == 0 || previous.is_some_and(|previous| previous >= node) {
+                return Err(ClockError::InvalidEncoding);
+            }
+            clock.entries.insert(node, counter);
+            previous = Some(node);
+            rest = remaining;
+        }
+        Ok(clock)
+    }
+}
+
+/// A hybrid logical clock paired with a vector clock.
+///
+/// The vector clock tracks causality: one `HlcVector` happened before another exactly
+/// when its history is contained in the other's, and two where neither happened before
+/// the other are concurrent. The hybrid logical clock gives every event a physical time
+/// for display and a total order consistent with causality.
+///
+/// The [`PartialEq`] and [`PartialOrd`] implementations compare histories only, and
+/// `partial_cmp` returns `None` for concurrent events.
+///
+/// # Example
+///
+/// ```
+/// use hybrid_logical_clock::vector::HlcVector;
+///
+/// let mut alice = HlcVector::new("alice", 1000);
+/// let mut bob = HlcVector::new("bob", 1000);
+///
+/// alice.tick(1001);
+/// bob.receive(&alice, 1002);
+/// assert!(alice.happened_before(&bob));
+///
+/// alice.tick(1003);
+/// bob.tick(1003);
+/// assert!(alice.concurrent_with(&bob));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HlcVector<N = NodeId> {
+    node: N,
+    hlc: HybridLogicalClock,
+    vector: VectorClock<N>,
+}
+
+impl<N: Ord + Clone> HlcVector<N> {
+    /// Creates a new HlcVector for `node` with an empty history, starting its clock at
+    /// physical time `now`.
+    pub fn new(node: N, now: u64) -> Self {
+        Self {
+            node,
+            hlc: HybridLogicalClock::new(now),
+            vector: VectorClock::new(),
+        }
+    }
+
+    /// Records a local or send event and returns its clock value.
+    ///
+    /// The node's own entry in the vector clock is incremented and the clock advances as
+    /// in [`HybridLogicalClock::tick`].
+    pub fn tick(&mut self, now: u64) -> HybridLogicalClock {
+        self.vector.increment(self.node.clone());
+        self.hlc.tick(now)
+    }
+
+    /// Records the receipt of a message carrying `received` and returns the clock value of
+    /// the receive event.
+    ///
+    /// The vector clocks are merged, the node's own entry is incremented, and the clock
+    /// advances as in [`HybridLogicalClock::update`].
+    pub fn receive(&mut self, received: &Self, now: u64) -> HybridLogicalClock {
+        self.vector.merge(&received.vector);
+        self.vector.increment(self.node.clone());
+        self.hlc.update(&received.hlc, now)
+    }
+
+    /// Returns true if this event happened before `other`.
+    pub fn happened_before(&self, other: &Self) -> bool {
+        self.vector.happened_before(&other.vector)
+    }
+
+    /// Returns true if neither event happened before the other.
+    pub fn concurrent_with(&self, other: &Self) -> bool {
+        self.vector.concurrent_with(&other.vector)
+    }
+
+    /// Returns the node this clock belongs to.
+    pub fn node(&self) -> &N {
+        &self.node
+    }
+
+    /// Returns the hybrid logical clock of the last recorded event.
+    pub fn hlc(&self) -> HybridLogicalClock {
+        self.hlc
+    }
+
+    /// Returns the vector clock of the last recorded event.
+    pub fn vector(&self) -> &VectorClock<N> {
+        &self.vector
+    }
+
+    /// Returns how many events from `node` are in this clock's history.
+    pub fn events_from(&self, node: &N) -> u64 {
+        self.vector.get(node)
+    }
+
+    /// Keeps only the vector clock entries for which `keep` returns true.
+    ///
+    /// See [`VectorClock::retain`] for when this is safe.
+    pub fn retain(&mut self, keep: impl FnMut(&N, u64) -> bool) {
+        self.vector.retain(keep);
+    }
+}
+
+impl HlcVector<NodeId> {
+    /// Returns the timestamp of the last recorded event.
+    pub fn timestamp(&self) -> Timestamp {
+        Timestamp::new(self.hlc, self.node)
+    }
+}
+
+impl<N: Ord + Clone> PartialEq for HlcVector<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.vector == other.vector
+    }
+}
+
+impl<N: Ord + Clone> Eq for HlcVector<N> {}
+
+impl<N: Ord + Clone> PartialOrd for HlcVector<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.vector.partial_cmp(&other.vector)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_vector_clock() {
+        let mut a = VectorClock::new();
+        let mut b = VectorClock::new();
+        assert_eq!(a, b);
+        assert_eq!(a.increment(NodeId(1)), 1);
+        assert!(b.happened_before(&a));
+        b.increment(NodeId(2));
+        assert!(a.concurrent_with(&b));
+
+        a.merge(&b);
+        assert_eq!(a.get(&NodeId(1)), 1);
+        assert_eq!(a.get(&NodeId(2)), 1);
+        assert!(b < a);
+
+        a.retain(|node, _| *node != NodeId(2));
+        assert_eq!(a.len(), 1);
+        assert_eq!(a.remove(&NodeId(1)), 1);
+        assert!(a.is_empty());
+
+        let collected: VectorClock<&str> = [("x", 0), ("y", 2), ("y", 1)].into_iter().collect();
+        assert_eq!(collected.iter().collect::<Vec<_>>(), [(&"y", 2)]);
+    }
+
+    #[test]
+    fn test_hlc_vector() {
+        let mut a = HlcVector::new(NodeId(1), 0);
+        let mut b = HlcVector::new(NodeId(2), 0);
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Equal));
+
+        a.tick(1);
+        assert!(b.happened_before(&a));
+        assert!(!a.happened_before(&b));
+
+        b.tick(1);
+        assert!(a.concurrent_with(&b));
+        assert!(b.concurrent_with(&a));
+        // The hybrid logical clocks are equal, yet the events are concurrent.
+        assert_eq!(a.hlc(), b.hlc());
+
+        let before = a.clone();
+        a.receive(&b, 0);
+        assert!(before.happened_before(&a));
+        assert!(b.happened_before(&a));
+        assert!(a.timestamp() > b.timestamp());
+        assert_eq!(a.events_from(&NodeId(1)), 2);
+        assert_eq!(a.events_from(&NodeId(2)), 1);
+
+        a.retain(|node, _| *node == NodeId(1));
+        assert_eq!(a.vector().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_encodings() {
+        let clock: VectorClock = [(NodeId(1), 3), (NodeId(2), 1)].into_iter().collect();
+        let bytes = clock.to_bytes();
+        assert_eq!(
+            VectorClock::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ClockError::BufferTooSmall {
+                needed: 36,
+                actual: 35
+            })
+        );
+        assert_eq!(
+            VectorClock::from_bytes(&[0, 0]),
+            Err(ClockError::BufferTooSmall {
+                needed: 4,
+                actual: 2
+            })
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            VectorClock::from_bytes(&trailing),
+            Err(ClockError::InvalidEncoding)
+        );
+
+        let mut unsorted = bytes.clone();
+        unsorted[4..12].copy_from_slice(&2u64.to_be_bytes());
+        assert_eq!(
+            VectorClock::from_bytes(&unsorted),
+            Err(ClockError::InvalidEncoding)
+        );
+
+        let mut zero = bytes;
+        zero[12..20].copy_from_slice(&0u64.to_be_bytes());
+        assert_eq!(
+            VectorClock::from_bytes(&zero),
+            Err(ClockError::InvalidEncoding)
+        );
+    }
+
+    /// One step of a random execution: a local event on a node, or a message delivery
+    /// from one node to another.
+    #[derive(Debug, Clone)]
+    enum Step {
+        Local(usize),
+        Deliver(usize, usize),
+    }
+
+    fn steps() -> impl Strategy<Value = Vec<Step>> {
+        proptest::collection::vec(
+            prop_oneof![
+                (0usize..3).prop_map(Step::Local),
+                (0usize..3, 0usize..3).prop_map(|(from, to)| Step::Deliver(from, to)),
+            ],
+            0..24,
+        )
+    }
+
+    fn vector_clock() -> impl Strategy<Value = VectorClock> {
+        proptest::collection::vec((0u64..4, 0u64..4), 0..4)
+            .prop_map(|entries| entries.into_iter().map(|(n, c)| (NodeId(n), c)).collect())
+    }
+
+    proptest! {
+        /// Checks the relations against the explicit set of events in each history.
+        #[test]
+        fn prop_matches_causal_histories(steps in steps(), nows in proptest::collection::vec(0u64..8, 24)) {
+            let mut clocks: Vec<HlcVector> =
+                (0..3).map(|node| HlcVector::new(NodeId(node), 0)).collect();
+            let mut histories: Vec<Vec<(u64, u64)>> = std::vec![Vec::new(); 3];
+            let mut snapshots = Vec::new();
+
+            for (step, now) in steps.into_iter().zip(nows) {
+                let node = match step {
+                    Step::Local(node) => {
+                        clocks[node].tick(now);
+                        node
+                    }
+                    Step::Deliver(from, to) => {
+                        let sent = clocks[from].clone();
+                        clocks[to].receive(&sent, now);
+                        let sent_history = histories[from].clone();
+                        for event in sent_history {
+                            if !histories[to].contains(&event) {
+                                histories[to].push(event);
+                            }
+                        }
+                        to
+                    }
+                };
+                let event = (node as u64, clocks[node].events_from(&NodeId(node as u64)));
+                histories[node].push(event);
+                snapshots.push((clocks[node].clone(), histories[node].clone()));
+            }
+
+            for (a, history_a) in &snapshots {
+                for (b, history_b) in &snapshots {
+                    let a_in_b = history_a.iter().all(|event| history_b.contains(event));
+                    let b_in_a = history_b.iter().all(|event| history_a.contains(event));
+                    prop_assert_eq!(a.happened_before(b), a_in_b && !b_in_a);
+                    prop_assert_eq!(a.concurrent_with(b), !a_in_b && !b_in_a);
+                    // Hybrid logical clocks are consistent with causality.
+                    if a.happened_before(b) {
+                        prop_assert!(a.timestamp() < b.timestamp());
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn prop_merge_is_a_join(a in vector_clock(), b in vector_clock(), c in vector_clock()) {
+            let mut ab = a.clone();
+            ab.merge(&b);
+            let mut ba = b.clone();
+            ba.merge(&a);
+            prop_assert_eq!(&ab, &ba);
+            prop_assert!(a <= ab && b <= ab);
+
+            let mut abc = ab.clone();
+            abc.merge(&c);
+            let mut bc = b.clone();
+            bc.merge(&c);
+            let mut a_bc = a.clone();
+            a_bc.merge(&bc);
+            prop_assert_eq!(&abc, &a_bc);
+
+            let mut aa = a.clone();
+            aa.merge(&a);
+            prop_assert_eq!(&aa, &a);
+        }
+
+        #[test]
+        fn prop_bytes_round_trip(clock in vector_clock()) {
+            prop_assert_eq!(VectorClock::from_bytes(&clock.to_bytes()), Ok(clock));
+        }
+    }
+}